@@ -0,0 +1,295 @@
+//! Affine matrix math for rotating and scaling backgrounds and objects.
+//!
+//! Angles are given as a `u16` where `0x1_0000` would be one full turn, the
+//! same as the BIOS affine functions. Only the upper 8 bits of an angle are
+//! used for the lookup, so there are 256 steps per turn.
+//!
+//! The GBA's affine matrices map from screen space into texture space, which
+//! is the inverse of what you might expect. So a scale of 2.0 makes things
+//! appear _half_ as big. A positive angle turns the image counter-clockwise
+//! on screen.
+
+use crate::data::{FP_I_19_8, FP_I_7_8};
+
+/// Sine of each of the 256 angle steps, with 12 fractional bits.
+pub const SIN_LUT: [i16; 256] = [
+  0, 101, 201, 301, 401, 501, 601, 700, 799, 897, 995, 1092, 1189, 1285, 1380, 1474, 1567, 1660,
+  1751, 1842, 1931, 2019, 2106, 2191, 2276, 2359, 2440, 2520, 2598, 2675, 2751, 2824, 2896, 2967,
+  3035, 3102, 3166, 3229, 3290, 3349, 3406, 3461, 3513, 3564, 3612, 3659, 3703, 3745, 3784, 3822,
+  3857, 3889, 3920, 3948, 3973, 3996, 4017, 4036, 4052, 4065, 4076, 4085, 4091, 4095, 4096, 4095,
+  4091, 4085, 4076, 4065, 4052, 4036, 4017, 3996, 3973, 3948, 3920, 3889, 3857, 3822, 3784, 3745,
+  3703, 3659, 3612, 3564, 3513, 3461, 3406, 3349, 3290, 3229, 3166, 3102, 3035, 2967, 2896, 2824,
+  2751, 2675, 2598, 2520, 2440, 2359, 2276, 2191, 2106, 2019, 1931, 1842, 1751, 1660, 1567, 1474,
+  1380, 1285, 1189, 1092, 995, 897, 799, 700, 601, 501, 401, 301, 201, 101, 0, -101, -201, -301,
+  -401, -501, -601, -700, -799, -897, -995, -1092, -1189, -1285, -1380, -1474, -1567, -1660, -1751,
+  -1842, -1931, -2019, -2106, -2191, -2276, -2359, -2440, -2520, -2598, -2675, -2751, -2824, -2896,
+  -2967, -3035, -3102, -3166, -3229, -3290, -3349, -3406, -3461, -3513, -3564, -3612, -3659, -3703,
+  -3745, -3784, -3822, -3857, -3889, -3920, -3948, -3973, -3996, -4017, -4036, -4052, -4065, -4076,
+  -4085, -4091, -4095, -4096, -4095, -4091, -4085, -4076, -4065, -4052, -4036, -4017, -3996, -3973,
+  -3948, -3920, -3889, -3857, -3822, -3784, -3745, -3703, -3659, -3612, -3564, -3513, -3461, -3406,
+  -3349, -3290, -3229, -3166, -3102, -3035, -2967, -2896, -2824, -2751, -2675, -2598, -2520, -2440,
+  -2359, -2276, -2191, -2106, -2019, -1931, -1842, -1751, -1660, -1567, -1474, -1380, -1285, -1189,
+  -1092, -995, -897, -799, -700, -601, -501, -401, -301, -201, -101,
+];
+
+/// The sine of an angle, with 12 fractional bits.
+pub fn sin_lut(angle: u16) -> i16 {
+  SIN_LUT[usize::from(angle >> 8)]
+}
+
+/// The cosine of an angle, with 12 fractional bits.
+pub fn cos_lut(angle: u16) -> i16 {
+  SIN_LUT[usize::from(angle.wrapping_add(0x4000) >> 8)]
+}
+
+/// The sine of an angle.
+pub fn sin(angle: u16) -> FP_I_7_8 {
+  FP_I_7_8(((i32::from(sin_lut(angle)) + 8) >> 4) as i16)
+}
+
+/// The cosine of an angle.
+pub fn cos(angle: u16) -> FP_I_7_8 {
+  FP_I_7_8(((i32::from(cos_lut(angle)) + 8) >> 4) as i16)
+}
+
+/// Multiplies a fixed point value by a 12 fractional bit trig value.
+fn scale_trig(scale: FP_I_7_8, trig: i16) -> FP_I_7_8 {
+  FP_I_7_8(((i32::from(scale.0) * i32::from(trig) + 0x800) >> 12) as i16)
+}
+
+/// A 2x2 affine transformation matrix.
+///
+/// ```text
+/// | pa pb |
+/// | pc pd |
+/// ```
+///
+/// For each screen pixel, the texture position is found by moving `pa` along
+/// the texture's x and `pc` along its y for each step right on the screen, and
+/// `pb` along x and `pd` along y for each step down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AffineMatrix {
+  /// Texture x change per screen x step.
+  pub pa: FP_I_7_8,
+  /// Texture x change per screen y step.
+  pub pb: FP_I_7_8,
+  /// Texture y change per screen x step.
+  pub pc: FP_I_7_8,
+  /// Texture y change per screen y step.
+  pub pd: FP_I_7_8,
+}
+impl AffineMatrix {
+  /// The identity matrix, which draws the texture as-is.
+  pub const IDENTITY: Self =
+    Self { pa: FP_I_7_8::ONE, pb: FP_I_7_8(0), pc: FP_I_7_8(0), pd: FP_I_7_8::ONE };
+
+  /// Makes a matrix from its parts.
+  pub const fn new(pa: FP_I_7_8, pb: FP_I_7_8, pc: FP_I_7_8, pd: FP_I_7_8) -> Self {
+    Self { pa, pb, pc, pd }
+  }
+
+  /// The identity matrix.
+  pub const fn identity() -> Self {
+    Self::IDENTITY
+  }
+
+  /// A rotation and scale, the same as the BIOS `ObjAffineSet`.
+  ///
+  /// The scales are texture pixels per screen pixel (see the module docs).
+  /// Parts that are out of range wrap, the same as with `Mul`.
+  pub fn rotation_scale(angle: u16, scale_x: FP_I_7_8, scale_y: FP_I_7_8) -> Self {
+    let sin = sin_lut(angle);
+    let cos = cos_lut(angle);
+    Self {
+      pa: scale_trig(scale_x, cos),
+      pb: scale_trig(scale_x, sin).wrapping_neg(),
+      pc: scale_trig(scale_y, sin),
+      pd: scale_trig(scale_y, cos),
+    }
+  }
+
+  /// A rotation with no scaling.
+  pub fn rotation(angle: u16) -> Self {
+    Self::rotation_scale(angle, FP_I_7_8::ONE, FP_I_7_8::ONE)
+  }
+
+  /// A scale with no rotation.
+  ///
+  /// The scales are texture pixels per screen pixel (see the module docs).
+  pub const fn scale(scale_x: FP_I_7_8, scale_y: FP_I_7_8) -> Self {
+    Self { pa: scale_x, pb: FP_I_7_8(0), pc: FP_I_7_8(0), pd: scale_y }
+  }
+
+  /// The determinant, with 16 fractional bits.
+  fn determinant(&self) -> i32 {
+    i32::from(self.pa.0) * i32::from(self.pd.0) - i32::from(self.pb.0) * i32::from(self.pc.0)
+  }
+
+  /// The inverse matrix, if there is one and it fits in range.
+  pub fn inverse(&self) -> Option<Self> {
+    let det = i64::from(self.determinant());
+    if det == 0 {
+      return None;
+    }
+    let part = |value: i16| -> Option<FP_I_7_8> {
+      // An 8 fractional bit value divided by a 16 fractional bit determinant
+      // needs 16 more bits to come out with 8 fractional bits.
+      let num = i64::from(value) << 16;
+      let quotient =
+        if (num < 0) == (det < 0) { (num + det / 2) / det } else { (num - det / 2) / det };
+      if quotient >= i64::from(i16::min_value()) && quotient <= i64::from(i16::max_value()) {
+        Some(FP_I_7_8(quotient as i16))
+      } else {
+        None
+      }
+    };
+    Some(Self {
+      pa: part(self.pd.0)?,
+      pb: part(self.pb.0.checked_neg()?)?,
+      pc: part(self.pc.0.checked_neg()?)?,
+      pd: part(self.pa.0)?,
+    })
+  }
+
+  /// Transforms a screen space offset into a texture space offset.
+  pub fn apply(&self, x: FP_I_19_8, y: FP_I_19_8) -> (FP_I_19_8, FP_I_19_8) {
+    let tx = i64::from(self.pa.0) * i64::from(x.0) + i64::from(self.pb.0) * i64::from(y.0);
+    let ty = i64::from(self.pc.0) * i64::from(x.0) + i64::from(self.pd.0) * i64::from(y.0);
+    (FP_I_19_8(((tx + 0x80) >> 8) as i32), FP_I_19_8(((ty + 0x80) >> 8) as i32))
+  }
+}
+impl Default for AffineMatrix {
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+impl core::ops::Mul for AffineMatrix {
+  type Output = Self;
+  /// The matrix product, rounding each part to the nearest 256th (and wrapping
+  /// if it's out of range).
+  fn mul(self, rhs: Self) -> Self {
+    let dot = |a: FP_I_7_8, b: FP_I_7_8, c: FP_I_7_8, d: FP_I_7_8| -> FP_I_7_8 {
+      let sum = i64::from(a.0) * i64::from(b.0) + i64::from(c.0) * i64::from(d.0);
+      FP_I_7_8(((sum + 0x80) >> 8) as i16)
+    };
+    Self {
+      pa: dot(self.pa, rhs.pa, self.pb, rhs.pc),
+      pb: dot(self.pa, rhs.pb, self.pb, rhs.pd),
+      pc: dot(self.pc, rhs.pa, self.pd, rhs.pc),
+      pd: dot(self.pc, rhs.pb, self.pd, rhs.pd),
+    }
+  }
+}
+
+/// The full set of affine background parameters: a matrix plus the texture
+/// position of the screen's top left pixel (`BGnX` and `BGnY`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BgAffineParams {
+  /// The matrix (`BGnPA` through `BGnPD`).
+  pub matrix: AffineMatrix,
+  /// The texture x of the top left screen pixel.
+  pub x: FP_I_19_8,
+  /// The texture y of the top left screen pixel.
+  pub y: FP_I_19_8,
+}
+impl BgAffineParams {
+  /// Rotates and scales around a center point, the same as the BIOS
+  /// `BgAffineSet`.
+  ///
+  /// The texture point `(tex_x, tex_y)` is drawn at the screen point
+  /// `(screen_x, screen_y)`, and everything else rotates and scales around
+  /// it. The scales are texture pixels per screen pixel (see the module docs).
+  pub fn rotation_scale(
+    tex_x: FP_I_19_8, tex_y: FP_I_19_8, screen_x: i16, screen_y: i16, scale_x: FP_I_7_8,
+    scale_y: FP_I_7_8, angle: u16,
+  ) -> Self {
+    let matrix = AffineMatrix::rotation_scale(angle, scale_x, scale_y);
+    let (dx, dy) =
+      matrix.apply(FP_I_19_8::from_int(screen_x.into()), FP_I_19_8::from_int(screen_y.into()));
+    Self {
+      matrix,
+      x: FP_I_19_8(tex_x.0.wrapping_sub(dx.0)),
+      y: FP_I_19_8(tex_y.0.wrapping_sub(dy.0)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn mul() {
+    let m = AffineMatrix::rotation_scale(0x2000, FP_I_7_8(0x180), FP_I_7_8(0x80));
+    assert_eq!(AffineMatrix::IDENTITY * m, m);
+    assert_eq!(m * AffineMatrix::IDENTITY, m);
+    let half = AffineMatrix::scale(FP_I_7_8(0x80), FP_I_7_8(0x80));
+    let double = AffineMatrix::scale(FP_I_7_8(0x200), FP_I_7_8(0x200));
+    assert_eq!(half * double, AffineMatrix::IDENTITY);
+  }
+
+  #[test]
+  fn rotation_scale() {
+    let fp = FP_I_7_8;
+    // The BIOS ObjAffineSet results at the quarter turns.
+    let m = |angle| AffineMatrix::rotation_scale(angle, fp(0x200), fp(0x80));
+    assert_eq!(m(0x0000), AffineMatrix::new(fp(0x200), fp(0), fp(0), fp(0x80)));
+    assert_eq!(m(0x4000), AffineMatrix::new(fp(0), fp(-0x200), fp(0x80), fp(0)));
+    assert_eq!(m(0x8000), AffineMatrix::new(fp(-0x200), fp(0), fp(0), fp(-0x80)));
+    // -(-128.0) wraps instead of overflowing.
+    let min = AffineMatrix::rotation_scale(0x4000, FP_I_7_8::MIN, FP_I_7_8::ONE);
+    assert_eq!(min.pb, FP_I_7_8::MIN);
+  }
+
+  #[test]
+  fn inverse() {
+    let fp = FP_I_7_8;
+    let m = AffineMatrix::scale(fp(0x200), fp(0x80));
+    assert_eq!(m.inverse(), Some(AffineMatrix::scale(fp(0x80), fp(0x200))));
+    assert_eq!(m * m.inverse().unwrap(), AffineMatrix::IDENTITY);
+    let r = AffineMatrix::rotation(0x4000);
+    assert_eq!(r.inverse(), Some(AffineMatrix::rotation(0xC000)));
+    assert_eq!(r * r.inverse().unwrap(), AffineMatrix::IDENTITY);
+    assert_eq!(AffineMatrix::IDENTITY.inverse(), Some(AffineMatrix::IDENTITY));
+    // The rows are multiples of each other.
+    let flat = AffineMatrix::new(fp(0x100), fp(0x200), fp(0x80), fp(0x100));
+    assert_eq!(flat.inverse(), None);
+    // 1/256 inverts to 256.0, which doesn't fit.
+    assert_eq!(AffineMatrix::scale(fp(1), fp(1)).inverse(), None);
+  }
+
+  #[test]
+  fn apply() {
+    let fp = FP_I_19_8::from_int;
+    let m = AffineMatrix::scale(FP_I_7_8(0x200), FP_I_7_8(0x80));
+    assert_eq!(m.apply(fp(10), fp(10)), (fp(20), fp(5)));
+    assert_eq!(AffineMatrix::rotation(0x4000).apply(fp(3), fp(0)), (fp(0), fp(3)));
+    // Rounds to the nearest 256th: 0.5 * 1/256.
+    let half = AffineMatrix::scale(FP_I_7_8(0x80), FP_I_7_8(0x80));
+    assert_eq!(half.apply(FP_I_19_8(1), FP_I_19_8(-1)), (FP_I_19_8(1), FP_I_19_8(0)));
+  }
+
+  #[test]
+  fn bg_rotation_scale() {
+    let fp = FP_I_19_8::from_int;
+    let scale = FP_I_7_8(0x180);
+    for &angle in &[0, 0x2000, 0x4000, 0x9000] {
+      let params = BgAffineParams::rotation_scale(fp(64), fp(32), 120, 80, scale, scale, angle);
+      // The texture point lands on the screen point.
+      let (dx, dy) = params.matrix.apply(fp(120), fp(80));
+      assert_eq!((params.x + dx, params.y + dy), (fp(64), fp(32)));
+    }
+    let quarter =
+      BgAffineParams::rotation_scale(fp(64), fp(32), 120, 80, FP_I_7_8::ONE, FP_I_7_8::ONE, 0x4000);
+    assert_eq!((quarter.x, quarter.y), (fp(144), fp(-88)));
+  }
+
+  #[test]
+  fn mul_wraps() {
+    let min = FP_I_7_8(i16::min_value());
+    let m = AffineMatrix::new(min, min, min, min);
+    // Each part is 2 * (-128.0)^2 = 32768.0, which wraps to 0.
+    let zero = FP_I_7_8(0);
+    assert_eq!(m * m, AffineMatrix::new(zero, zero, zero, zero));
+  }
+}
@@ -0,0 +1,238 @@
+//! Register access through a trait, so register code can run off the GBA.
+//!
+//! Code that's written against [RegisterBus] instead of the `mmio` constants
+//! can use the hardware implementation in the `mmio` module on the GBA, and a
+//! [MockBus] (with the `std` feature) in tests on any machine.
+
+/// The size of a register access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Width {
+  /// 8-bit
+  U8,
+  /// 16-bit
+  U16,
+  /// 32-bit
+  U32,
+}
+impl Width {
+  /// The number of bytes accessed.
+  pub const fn bytes(self) -> usize {
+    match self {
+      Width::U8 => 1,
+      Width::U16 => 2,
+      Width::U32 => 4,
+    }
+  }
+
+  /// Cuts a value down to this width.
+  pub const fn mask(self, value: u32) -> u32 {
+    match self {
+      Width::U8 => value & 0xFF,
+      Width::U16 => value & 0xFFFF,
+      Width::U32 => value,
+    }
+  }
+}
+
+/// Volatile reads and writes by address and width.
+///
+/// Addresses should be aligned to the width, the same as on the hardware.
+pub trait RegisterBus {
+  /// Reads a value. Only the low `width` bits of the result are used.
+  fn read(&mut self, address: usize, width: Width) -> u32;
+  /// Writes a value. Only the low `width` bits of `value` are used.
+  fn write(&mut self, address: usize, width: Width, value: u32);
+
+  /// Reads a `u8`.
+  fn read_u8(&mut self, address: usize) -> u8 {
+    self.read(address, Width::U8) as u8
+  }
+  /// Reads a `u16`.
+  fn read_u16(&mut self, address: usize) -> u16 {
+    self.read(address, Width::U16) as u16
+  }
+  /// Reads a `u32`.
+  fn read_u32(&mut self, address: usize) -> u32 {
+    self.read(address, Width::U32)
+  }
+  /// Writes a `u8`.
+  fn write_u8(&mut self, address: usize, value: u8) {
+    self.write(address, Width::U8, u32::from(value))
+  }
+  /// Writes a `u16`.
+  fn write_u16(&mut self, address: usize, value: u16) {
+    self.write(address, Width::U16, u32::from(value))
+  }
+  /// Writes a `u32`.
+  fn write_u32(&mut self, address: usize, value: u32) {
+    self.write(address, Width::U32, value)
+  }
+  /// Reads a value, changes it, and writes it back.
+  fn modify_u16(&mut self, address: usize, f: impl FnOnce(u16) -> u16) {
+    let value = self.read_u16(address);
+    self.write_u16(address, f(value))
+  }
+}
+
+#[cfg(any(test, feature = "std"))]
+pub use mock::*;
+
+#[cfg(any(test, feature = "std"))]
+mod mock {
+  use super::{RegisterBus, Width};
+  use std::{
+    collections::{BTreeMap, VecDeque},
+    vec::Vec,
+  };
+
+  /// If an access was a read or a write.
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  pub enum AccessKind {
+    /// A read
+    Read,
+    /// A write
+    Write,
+  }
+
+  /// One access made through a [MockBus].
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  pub struct Access {
+    /// Read or write.
+    pub kind: AccessKind,
+    /// The address.
+    pub address: usize,
+    /// The width.
+    pub width: Width,
+    /// The value read or written.
+    pub value: u32,
+  }
+
+  /// A [RegisterBus] that records every access.
+  ///
+  /// The mock is plain memory (little-endian, everything starts as 0) so that
+  /// writes read back, but you can also queue up values for the next reads of
+  /// an address, such as a status register that changes while being polled.
+  #[derive(Debug, Clone, Default)]
+  pub struct MockBus {
+    memory: BTreeMap<usize, u8>,
+    queued: BTreeMap<(usize, Width), VecDeque<u32>>,
+    accesses: Vec<Access>,
+  }
+  impl MockBus {
+    /// Makes a mock with all memory 0 and no accesses.
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    /// Sets memory without recording an access.
+    pub fn set(&mut self, address: usize, width: Width, value: u32) {
+      for i in 0..width.bytes() {
+        self.memory.insert(address + i, (value >> (i * 8)) as u8);
+      }
+    }
+
+    /// Gets memory without recording an access.
+    pub fn get(&self, address: usize, width: Width) -> u32 {
+      (0..width.bytes()).fold(0, |acc, i| {
+        acc | u32::from(self.memory.get(&(address + i)).copied().unwrap_or(0)) << (i * 8)
+      })
+    }
+
+    /// Queues a value for a later read of this address and width.
+    ///
+    /// Queued values are used in order, one per read, before falling back to
+    /// memory. They don't change memory, and are cut down to the width when
+    /// read.
+    pub fn queue_read(&mut self, address: usize, width: Width, value: u32) {
+      self.queued.entry((address, width)).or_default().push_back(value);
+    }
+
+    /// Every access so far, oldest first.
+    pub fn accesses(&self) -> &[Access] {
+      &self.accesses
+    }
+
+    /// The values written to an address, oldest first.
+    pub fn writes_to(&self, address: usize) -> Vec<u32> {
+      self
+        .accesses
+        .iter()
+        .filter(|a| a.kind == AccessKind::Write && a.address == address)
+        .map(|a| a.value)
+        .collect()
+    }
+
+    /// Forgets the accesses so far (memory is kept).
+    pub fn clear_accesses(&mut self) {
+      self.accesses.clear();
+    }
+  }
+  impl RegisterBus for MockBus {
+    fn read(&mut self, address: usize, width: Width) -> u32 {
+      let queued = self.queued.get_mut(&(address, width)).and_then(|q| q.pop_front());
+      let value = width.mask(queued.unwrap_or_else(|| self.get(address, width)));
+      self.accesses.push(Access { kind: AccessKind::Read, address, width, value });
+      value
+    }
+
+    fn write(&mut self, address: usize, width: Width, value: u32) {
+      let value = width.mask(value);
+      self.set(address, width, value);
+      self.accesses.push(Access { kind: AccessKind::Write, address, width, value });
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn records_accesses() {
+    let mut bus = MockBus::new();
+    bus.write_u16(0x400_0000, 0x0403);
+    assert_eq!(bus.read_u8(0x400_0001), 0x04);
+    bus.modify_u16(0x400_0000, |v| v | 0x80);
+    assert_eq!(
+      bus.accesses(),
+      &[
+        Access { kind: AccessKind::Write, address: 0x400_0000, width: Width::U16, value: 0x0403 },
+        Access { kind: AccessKind::Read, address: 0x400_0001, width: Width::U8, value: 0x04 },
+        Access { kind: AccessKind::Read, address: 0x400_0000, width: Width::U16, value: 0x0403 },
+        Access { kind: AccessKind::Write, address: 0x400_0000, width: Width::U16, value: 0x0483 },
+      ]
+    );
+    assert_eq!(bus.writes_to(0x400_0000), [0x0403, 0x0483]);
+    bus.clear_accesses();
+    assert!(bus.accesses().is_empty());
+    assert_eq!(bus.get(0x400_0000, Width::U32), 0x0483);
+  }
+
+  #[test]
+  fn queued_reads() {
+    let mut bus = MockBus::new();
+    bus.set(0x400_0004, Width::U16, 1);
+    bus.queue_read(0x400_0004, Width::U16, 2);
+    bus.queue_read(0x400_0004, Width::U16, 3);
+    // Queued values are per width, and don't change memory.
+    assert_eq!(bus.read_u8(0x400_0004), 1);
+    assert_eq!(bus.read_u16(0x400_0004), 2);
+    assert_eq!(bus.read_u16(0x400_0004), 3);
+    assert_eq!(bus.read_u16(0x400_0004), 1);
+  }
+
+  #[test]
+  fn width_masking() {
+    assert_eq!(Width::U8.mask(0x1234_5678), 0x78);
+    assert_eq!(Width::U16.mask(0x1234_5678), 0x5678);
+    assert_eq!(Width::U32.mask(0x1234_5678), 0x1234_5678);
+    let mut bus = MockBus::new();
+    bus.write(0x400_0008, Width::U8, 0x1234_5678);
+    bus.write(0x400_000A, Width::U16, 0xABCD_EF01);
+    assert_eq!(bus.writes_to(0x400_0008), [0x78]);
+    assert_eq!(bus.writes_to(0x400_000A), [0xEF01]);
+    assert_eq!(bus.get(0x400_0008, Width::U32), 0xEF01_0078);
+    bus.queue_read(0x400_0008, Width::U8, 0x1FF);
+    assert_eq!(bus.read(0x400_0008, Width::U8), 0xFF);
+  }
+}
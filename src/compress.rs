@@ -0,0 +1,569 @@
+//! Compression into the data formats that the GBA BIOS understands.
+//!
+//! This is the other half of the [decompress](crate::decompress) module, and
+//! it's intended for use in build scripts and other host side tools. Each
+//! function gives back the full compressed data, including the 4-byte header,
+//! padded to a multiple of 4 bytes (the BIOS needs compressed data to be
+//! aligned to 4, so it's easiest if the length is also a multiple of 4).
+//!
+//! ```
+//! use gba_hal::{compress::lz77_comp, decompress::lz77_uncomp};
+//!
+//! let data = b"tiles tiles tiles tiles";
+//! let compressed = lz77_comp(data).unwrap();
+//! let mut out = [0_u8; 23];
+//! assert_eq!(lz77_uncomp(&compressed, &mut out), Ok(data.len()));
+//! assert_eq!(&out[..], &data[..]);
+//! ```
+//!
+//! This module requires the `std` feature.
+
+use super::decompress::CompressionHeader;
+use std::vec::Vec;
+
+/// The ways that compression can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressError {
+  /// The input is too large for the header's 24-bit size field.
+  InputTooLarge,
+  /// Huffman data units must be 4 or 8 bits.
+  BadUnitBits(u8),
+  /// 16-bit Diff filter input must be an even number of bytes.
+  OddLength,
+  /// The Huffman tree couldn't be arranged so that every node offset fits in
+  /// the 6 bits available.
+  TreeLayout,
+}
+
+/// Starts the output with the header for the given type.
+fn start_output(kind: u32, parameter: u32, data_len: usize) -> Result<Vec<u8>, CompressError> {
+  if data_len > 0xFF_FFFF {
+    return Err(CompressError::InputTooLarge);
+  }
+  let header = CompressionHeader::new()
+    .with_kind(kind)
+    .with_parameter(parameter)
+    .with_decompressed_size(data_len as u32);
+  let mut out = Vec::with_capacity(data_len + 8);
+  out.extend_from_slice(&header.0.to_le_bytes());
+  Ok(out)
+}
+
+/// Pads the output out to a multiple of 4 bytes.
+fn finish_output(mut out: Vec<u8>) -> Vec<u8> {
+  while out.len() % 4 != 0 {
+    out.push(0);
+  }
+  out
+}
+
+/// LZ77 compression (type `0x10`).
+///
+/// Back references are never made with a distance of 1 byte, so the output
+/// can be decompressed with either `lz77_uncomp` or `lz77_uncomp_vram`.
+pub fn lz77_comp(data: &[u8]) -> Result<Vec<u8>, CompressError> {
+  const WINDOW: usize = 0x1000;
+  const MIN_LEN: usize = 3;
+  const MAX_LEN: usize = 18;
+  const NO_POS: usize = usize::max_value();
+  let mut out = start_output(CompressionHeader::LZ77, 0, data.len())?;
+  // Chains of earlier positions that start with the same three bytes, so that
+  // we don't need to search the whole window.
+  let mut head = [NO_POS; 0x1000];
+  let mut prev = std::vec![NO_POS; data.len()];
+  //
+  let mut pos = 0;
+  let mut flag_index = 0;
+  let mut block = 8;
+  while pos < data.len() {
+    if block == 8 {
+      flag_index = out.len();
+      out.push(0);
+      block = 0;
+    }
+    let mut best_len = 0;
+    let mut best_dist = 0;
+    if pos + MIN_LEN <= data.len() {
+      let max_len = MAX_LEN.min(data.len() - pos);
+      let mut candidate = head[lz77_hash(data, pos)];
+      while candidate != NO_POS && pos - candidate <= WINDOW {
+        let dist = pos - candidate;
+        if dist >= 2 {
+          let len = (0..max_len).take_while(|&i| data[candidate + i] == data[pos + i]).count();
+          if len > best_len {
+            best_len = len;
+            best_dist = dist;
+            if len == max_len {
+              break;
+            }
+          }
+        }
+        candidate = prev[candidate];
+      }
+    }
+    if best_len >= MIN_LEN {
+      out[flag_index] |= 0x80 >> block;
+      let d = best_dist - 1;
+      out.push((((best_len - MIN_LEN) << 4) | (d >> 8)) as u8);
+      out.push(d as u8);
+      for i in pos..pos + best_len {
+        lz77_insert(data, i, &mut head, &mut prev);
+      }
+      pos += best_len;
+    } else {
+      out.push(data[pos]);
+      lz77_insert(data, pos, &mut head, &mut prev);
+      pos += 1;
+    }
+    block += 1;
+  }
+  Ok(finish_output(out))
+}
+
+/// Hashes the three bytes starting at `i`.
+fn lz77_hash(data: &[u8], i: usize) -> usize {
+  ((data[i] as usize) << 8 ^ (data[i + 1] as usize) << 4 ^ data[i + 2] as usize) & 0xFFF
+}
+
+/// Adds position `i` to the front of its hash chain.
+fn lz77_insert(data: &[u8], i: usize, head: &mut [usize; 0x1000], prev: &mut [usize]) {
+  if i + 3 <= data.len() {
+    let h = lz77_hash(data, i);
+    prev[i] = head[h];
+    head[h] = i;
+  }
+}
+
+/// Run-Length compression (type `0x30`).
+pub fn rl_comp(data: &[u8]) -> Result<Vec<u8>, CompressError> {
+  const MIN_RUN: usize = 3;
+  const MAX_RUN: usize = 0x7F + MIN_RUN;
+  const MAX_LITERAL: usize = 0x80;
+  let mut out = start_output(CompressionHeader::RLE, 0, data.len())?;
+  let run_at = |i: usize| data[i..].iter().take(MAX_RUN).take_while(|&&b| b == data[i]).count();
+  let mut pos = 0;
+  while pos < data.len() {
+    let run = run_at(pos);
+    if run >= MIN_RUN {
+      out.push(0x80 | (run - MIN_RUN) as u8);
+      out.push(data[pos]);
+      pos += run;
+    } else {
+      let start = pos;
+      while pos < data.len() && pos - start < MAX_LITERAL && run_at(pos) < MIN_RUN {
+        pos += 1;
+      }
+      out.push((pos - start - 1) as u8);
+      out.extend_from_slice(&data[start..pos]);
+    }
+  }
+  Ok(finish_output(out))
+}
+
+/// 8-bit Diff filter (type `0x81`).
+///
+/// This doesn't make the data smaller by itself, but it can make it compress
+/// better afterwards.
+pub fn diff8bit_filter(data: &[u8]) -> Result<Vec<u8>, CompressError> {
+  let mut out = start_output(CompressionHeader::DIFF, 1, data.len())?;
+  let mut prev = 0_u8;
+  for &byte in data.iter() {
+    out.push(byte.wrapping_sub(prev));
+    prev = byte;
+  }
+  Ok(finish_output(out))
+}
+
+/// 16-bit Diff filter (type `0x82`).
+///
+/// The data is treated as little-endian `u16` values, so it must be an even
+/// number of bytes.
+pub fn diff16bit_filter(data: &[u8]) -> Result<Vec<u8>, CompressError> {
+  if data.len() % 2 != 0 {
+    return Err(CompressError::OddLength);
+  }
+  let mut out = start_output(CompressionHeader::DIFF, 2, data.len())?;
+  let mut prev = 0_u16;
+  for unit in data.chunks_exact(2) {
+    let unit = u16::from_le_bytes([unit[0], unit[1]]);
+    out.extend_from_slice(&unit.wrapping_sub(prev).to_le_bytes());
+    prev = unit;
+  }
+  Ok(finish_output(out))
+}
+
+/// A node of a Huffman tree while we're building it.
+#[derive(Debug, Clone, Copy)]
+enum HuffNode {
+  Leaf(u8),
+  Internal(usize, usize),
+}
+
+/// A pair of nodes stored together in the tree table.
+///
+/// The root is special, and gets a pair all to itself.
+struct HuffPair {
+  nodes: Vec<usize>,
+  kids: Vec<usize>,
+}
+
+/// Huffman compression (type `0x24` or `0x28`).
+///
+/// `bits` is the size of each data unit, either 4 or 8. With 4-bit units each
+/// byte is split into two units, low nibble first.
+pub fn huff_comp(data: &[u8], bits: u8) -> Result<Vec<u8>, CompressError> {
+  let units: Vec<u8> = match bits {
+    8 => data.to_vec(),
+    4 => {
+      let mut units = Vec::with_capacity(data.len() * 2);
+      for &byte in data.iter() {
+        units.push(byte & 0xF);
+        units.push(byte >> 4);
+      }
+      units
+    }
+    _ => return Err(CompressError::BadUnitBits(bits)),
+  };
+  let mut out = start_output(CompressionHeader::HUFFMAN, u32::from(bits), data.len())?;
+  //
+  let (nodes, root) = huff_build_tree(&units);
+  let mut codes = [(0_u32, 0_u32); 256];
+  huff_assign_codes(&nodes, root, 0, 0, &mut codes);
+  //
+  let mut pairs = std::vec![HuffPair { nodes: std::vec![root], kids: Vec::new() }];
+  let mut i = 0;
+  while i < pairs.len() {
+    for n in 0..pairs[i].nodes.len() {
+      if let HuffNode::Internal(left, right) = nodes[pairs[i].nodes[n]] {
+        let kid = pairs.len();
+        pairs.push(HuffPair { nodes: std::vec![left, right], kids: Vec::new() });
+        pairs[i].kids.push(kid);
+      }
+    }
+    i += 1;
+  }
+  let order = huff_layout(&pairs).ok_or(CompressError::TreeLayout)?;
+  let mut position = std::vec![0; pairs.len()];
+  for (pos, &pair) in order.iter().enumerate() {
+    position[pair] = pos;
+  }
+  // The tree size byte sits in front of the root, so with an odd number of
+  // pairs we add an empty pair to keep the bitstream aligned.
+  let table_pairs = pairs.len() + (pairs.len() % 2);
+  out.push((table_pairs - 1) as u8);
+  for &pair in order.iter() {
+    let mut kids = pairs[pair].kids.iter();
+    for &node in pairs[pair].nodes.iter() {
+      match nodes[node] {
+        HuffNode::Leaf(value) => out.push(value),
+        HuffNode::Internal(left, right) => {
+          let kid = *kids.next().unwrap();
+          let mut byte = (position[kid] - position[pair] - 1) as u8;
+          if let HuffNode::Leaf(_) = nodes[left] {
+            byte |= 0x80;
+          }
+          if let HuffNode::Leaf(_) = nodes[right] {
+            byte |= 0x40;
+          }
+          out.push(byte);
+        }
+      }
+    }
+  }
+  out.resize(4 + table_pairs * 2, 0);
+  //
+  let mut word = 0_u32;
+  let mut word_bits = 0;
+  for &unit in units.iter() {
+    let (code, len) = codes[unit as usize];
+    for bit in (0..len).rev() {
+      word |= ((code >> bit) & 1) << (31 - word_bits);
+      word_bits += 1;
+      if word_bits == 32 {
+        out.extend_from_slice(&word.to_le_bytes());
+        word = 0;
+        word_bits = 0;
+      }
+    }
+  }
+  if word_bits > 0 {
+    out.extend_from_slice(&word.to_le_bytes());
+  }
+  Ok(finish_output(out))
+}
+
+/// Builds the tree, giving the node list and the root index.
+fn huff_build_tree(units: &[u8]) -> (Vec<HuffNode>, usize) {
+  let mut counts = [0_usize; 256];
+  for &unit in units.iter() {
+    counts[unit as usize] += 1;
+  }
+  let mut nodes = Vec::new();
+  let mut weighted: Vec<(usize, usize)> = Vec::new();
+  for (value, &count) in counts.iter().enumerate() {
+    if count > 0 {
+      weighted.push((count, nodes.len()));
+      nodes.push(HuffNode::Leaf(value as u8));
+    }
+  }
+  // The root must be an internal node, so we need at least two leaves even if
+  // the data has fewer distinct values than that.
+  while weighted.len() < 2 {
+    let value = match nodes.first() {
+      Some(HuffNode::Leaf(value)) => value ^ 1,
+      _ => 0,
+    };
+    weighted.push((0, nodes.len()));
+    nodes.push(HuffNode::Leaf(value));
+  }
+  while weighted.len() > 1 {
+    weighted.sort_by(|a, b| b.cmp(a));
+    let (weight_a, a) = weighted.pop().unwrap();
+    let (weight_b, b) = weighted.pop().unwrap();
+    weighted.push((weight_a + weight_b, nodes.len()));
+    nodes.push(HuffNode::Internal(a, b));
+  }
+  let root = weighted[0].1;
+  (nodes, root)
+}
+
+/// Fills in the `(code, length)` of every leaf below this node.
+fn huff_assign_codes(
+  nodes: &[HuffNode], node: usize, code: u32, len: u32, codes: &mut [(u32, u32); 256],
+) {
+  match nodes[node] {
+    HuffNode::Leaf(value) => codes[value as usize] = (code, len),
+    HuffNode::Internal(left, right) => {
+      huff_assign_codes(nodes, left, code << 1, len + 1, codes);
+      huff_assign_codes(nodes, right, (code << 1) | 1, len + 1, codes);
+    }
+  }
+}
+
+/// The most pairs that a node offset can skip.
+const HUFF_MAX_GAP: usize = 64;
+
+/// Picks an order for the node pairs in the tree table.
+///
+/// The root pair must come first, and each pair's kids must come after it
+/// within 64 pairs (the 6-bit node offset). A simple breadth first order
+/// doesn't work for wide trees, so first we try a deadline based order that
+/// prefers to finish small subtrees, and if that fails we try ordering by
+/// position within the tree.
+fn huff_layout(pairs: &[HuffPair]) -> Option<Vec<usize>> {
+  // Subtree sizes, heights, and the index of the first leaf-only pair.
+  let mut size = std::vec![1_usize; pairs.len()];
+  let mut height = std::vec![0_usize; pairs.len()];
+  let mut first_leaf = std::vec![0_usize; pairs.len()];
+  for i in (0..pairs.len()).rev() {
+    for &kid in pairs[i].kids.iter() {
+      size[i] += size[kid];
+      height[i] = height[i].max(height[kid] + 1);
+    }
+  }
+  let mut leaf_count = 0;
+  let mut stack = std::vec![0];
+  while let Some(i) = stack.pop() {
+    first_leaf[i] = leaf_count;
+    if pairs[i].kids.is_empty() {
+      leaf_count += 1;
+    }
+    stack.extend(pairs[i].kids.iter().rev());
+  }
+  //
+  let mut order = huff_deadline_order(pairs, &size);
+  if huff_order_is_valid(pairs, &order) {
+    return Some(order);
+  }
+  for spread in (4..=64).step_by(4) {
+    order.sort_by_key(|&i| {
+      ((2 * first_leaf[i]) as isize - (spread * height[i]) as isize, !height[i], i)
+    });
+    if huff_order_is_valid(pairs, &order) {
+      return Some(order);
+    }
+  }
+  None
+}
+
+/// Orders the pairs by when they're due, otherwise smallest subtree first.
+fn huff_deadline_order(pairs: &[HuffPair], size: &[usize]) -> Vec<usize> {
+  let mut order = std::vec![0];
+  let mut pending: Vec<(usize, usize)> = pairs[0].kids.iter().map(|&kid| (0, kid)).collect();
+  while !pending.is_empty() {
+    let now = order.len();
+    let urgent = (0..pending.len()).min_by_key(|&i| pending[i]).unwrap();
+    let pick = if pending[urgent].0 + HUFF_MAX_GAP <= now {
+      urgent
+    } else {
+      (0..pending.len()).min_by_key(|&i| (size[pending[i].1], pending[i])).unwrap()
+    };
+    let (_, pair) = pending.swap_remove(pick);
+    order.push(pair);
+    pending.extend(pairs[pair].kids.iter().map(|&kid| (now, kid)));
+  }
+  order
+}
+
+/// Checks that the root is first and every node offset fits.
+fn huff_order_is_valid(pairs: &[HuffPair], order: &[usize]) -> bool {
+  let mut position = std::vec![0; pairs.len()];
+  for (pos, &pair) in order.iter().enumerate() {
+    position[pair] = pos;
+  }
+  order[0] == 0
+    && pairs.iter().enumerate().all(|(i, pair)| {
+      pair
+        .kids
+        .iter()
+        .all(|&kid| position[kid] > position[i] && position[kid] - position[i] <= HUFF_MAX_GAP)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::decompress::*;
+
+  /// Xorshift noise, which none of the formats can compress.
+  fn noise(len: usize) -> Vec<u8> {
+    let mut seed = 0x1234_5678_u32;
+    (0..len)
+      .map(|_| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as u8
+      })
+      .collect()
+  }
+
+  /// Empty, 1-byte, incompressible, and very compressible inputs.
+  fn inputs() -> Vec<Vec<u8>> {
+    std::vec![
+      Vec::new(),
+      std::vec![0x5A],
+      noise(300),
+      b"tiles tiles tiles tiles".to_vec(),
+      std::vec![7; 1000],
+    ]
+  }
+
+  fn round_trip(
+    data: &[u8], compressed: &[u8], uncomp: fn(&[u8], &mut [u8]) -> Result<usize, DecompressError>,
+  ) {
+    assert_eq!(compressed.len() % 4, 0);
+    let mut out = std::vec![0; data.len()];
+    assert_eq!(uncomp(compressed, &mut out), Ok(data.len()));
+    assert_eq!(&out[..], data);
+  }
+
+  #[test]
+  fn lz77() {
+    for data in inputs() {
+      let compressed = lz77_comp(&data).unwrap();
+      round_trip(&data, &compressed, lz77_uncomp);
+      if data.len() % 2 == 0 {
+        let mut out = std::vec![0_u16; data.len() / 2];
+        assert_eq!(lz77_uncomp_vram(&compressed, &mut out), Ok(data.len()));
+        let bytes: Vec<u8> = out.iter().flat_map(|u| u.to_le_bytes().to_vec()).collect();
+        assert_eq!(bytes, data);
+      }
+    }
+    assert!(lz77_comp(&[7; 1000]).unwrap().len() < 200);
+  }
+
+  #[test]
+  fn huffman() {
+    for &bits in [4, 8].iter() {
+      for data in inputs() {
+        let compressed = huff_comp(&data, bits).unwrap();
+        round_trip(&data, &compressed, huff_uncomp);
+      }
+      // Every unit value used, so the tree is as wide as it gets.
+      let all: Vec<u8> = (0..=255).collect();
+      round_trip(&all, &huff_comp(&all, bits).unwrap(), huff_uncomp);
+    }
+    assert_eq!(huff_comp(b"x", 5), Err(CompressError::BadUnitBits(5)));
+  }
+
+  /// Builds the pairs for a lopsided random tree, the same way `huff_comp`
+  /// does, giving back the pairs and the root's node index.
+  fn random_tree_pairs(leaves: usize, mut seed: u32) -> Vec<HuffPair> {
+    fn build(nodes: &mut Vec<HuffNode>, n: usize, seed: &mut u32) -> usize {
+      if n == 1 {
+        nodes.push(HuffNode::Leaf(nodes.len() as u8));
+        return nodes.len() - 1;
+      }
+      *seed ^= *seed << 13;
+      *seed ^= *seed >> 7;
+      *seed ^= *seed << 17;
+      let left_leaves = if *seed % 3 == 0 { n - 1 } else { n / 2 };
+      let left = build(nodes, left_leaves, seed);
+      let right = build(nodes, n - left_leaves, seed);
+      nodes.push(HuffNode::Internal(left, right));
+      nodes.len() - 1
+    }
+    let mut nodes = Vec::new();
+    let root = build(&mut nodes, leaves, &mut seed);
+    let mut pairs = std::vec![HuffPair { nodes: std::vec![root], kids: Vec::new() }];
+    let mut i = 0;
+    while i < pairs.len() {
+      for n in 0..pairs[i].nodes.len() {
+        if let HuffNode::Internal(left, right) = nodes[pairs[i].nodes[n]] {
+          let kid = pairs.len();
+          pairs.push(HuffPair { nodes: std::vec![left, right], kids: Vec::new() });
+          pairs[i].kids.push(kid);
+        }
+      }
+      i += 1;
+    }
+    pairs
+  }
+
+  #[test]
+  fn huffman_layout_fallback() {
+    let pairs = random_tree_pairs(256, 4);
+    let mut size = std::vec![1_usize; pairs.len()];
+    for i in (0..pairs.len()).rev() {
+      for &kid in pairs[i].kids.iter() {
+        size[i] += size[kid];
+      }
+    }
+    // This tree is one where the deadline order fails.
+    assert!(!huff_order_is_valid(&pairs, &huff_deadline_order(&pairs, &size)));
+    let order = huff_layout(&pairs).unwrap();
+    assert!(huff_order_is_valid(&pairs, &order));
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..pairs.len()).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn rle() {
+    for data in inputs() {
+      round_trip(&data, &rl_comp(&data).unwrap(), rl_uncomp);
+    }
+    // Runs longer than one block can hold.
+    let mut data = std::vec![1; 200];
+    data.extend_from_slice(&noise(200));
+    data.extend(std::iter::repeat(2).take(3));
+    round_trip(&data, &rl_comp(&data).unwrap(), rl_uncomp);
+  }
+
+  #[test]
+  fn diff() {
+    for data in inputs() {
+      round_trip(&data, &diff8bit_filter(&data).unwrap(), diff8bit_unfilter);
+      if data.len() % 2 == 0 {
+        let compressed = diff16bit_filter(&data).unwrap();
+        let mut out = std::vec![0_u16; data.len() / 2];
+        assert_eq!(diff16bit_unfilter(&compressed, &mut out), Ok(data.len()));
+        let bytes: Vec<u8> = out.iter().flat_map(|u| u.to_le_bytes().to_vec()).collect();
+        assert_eq!(bytes, data);
+      } else {
+        assert_eq!(diff16bit_filter(&data), Err(CompressError::OddLength));
+      }
+    }
+  }
+}
@@ -140,6 +140,106 @@ impl BackgroundControl {
   }
 }
 
+newtype! {
+  /// An entry in a text mode screenblock.
+  ///
+  /// * Bits 0-9: Tile Index (tile offset from the character base block)
+  /// * Bit 10: Horizontal flip
+  /// * Bit 11: Vertical flip
+  /// * Bits 12-15: Palbank (if using 4bpp)
+  TextScreenEntry, u16
+}
+#[allow(missing_docs)]
+impl TextScreenEntry {
+  phantom_fields! {
+    self.0: u16,
+    tile_id: 0-9,
+    hflip: 10,
+    vflip: 11,
+    palbank: 12-15,
+  }
+}
+
+newtype! {
+  /// An entry in an affine screenblock, which is just a tile index.
+  ///
+  /// Affine backgrounds are always 8bpp and can't flip tiles, so there's
+  /// nothing else to an entry. VRAM can't be written one byte at a time, so
+  /// entries have to be written in pairs (see `pack_pair`).
+  AffineScreenEntry, pub u8
+}
+impl AffineScreenEntry {
+  /// Packs two entries into the `u16` that holds them in VRAM.
+  ///
+  /// The `even` entry is the one at the even index (the lower address).
+  pub const fn pack_pair(even: Self, odd: Self) -> u16 {
+    (even.0 as u16) | ((odd.0 as u16) << 8)
+  }
+}
+
+/// An 8x8 tile with 4 bits per pixel.
+///
+/// Each `u32` is one row of the tile, with the leftmost pixel in the lowest
+/// 4 bits. Each pixel is an index into the tile's palbank, with 0 being
+/// transparent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Tile4bpp(pub [u32; 8]);
+impl Tile4bpp {
+  /// Gets the pixel at `(x, y)`.
+  ///
+  /// # Panics
+  ///
+  /// If `x` or `y` are 8 or more.
+  pub fn pixel(&self, x: usize, y: usize) -> u8 {
+    assert!(x < 8, "x out of range");
+    ((self.0[y] >> (x * 4)) & 0xF) as u8
+  }
+
+  /// Sets the pixel at `(x, y)`, using only the low 4 bits of the value.
+  ///
+  /// # Panics
+  ///
+  /// If `x` or `y` are 8 or more.
+  pub fn set_pixel(&mut self, x: usize, y: usize, value: u8) {
+    assert!(x < 8, "x out of range");
+    let shift = x * 4;
+    self.0[y] = (self.0[y] & !(0xF << shift)) | ((u32::from(value) & 0xF) << shift);
+  }
+}
+
+/// An 8x8 tile with 8 bits per pixel.
+///
+/// Each pair of `u32` values is one row of the tile, with the leftmost pixel
+/// in the lowest 8 bits of the first value. Each pixel is an index into the
+/// full palette, with 0 being transparent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Tile8bpp(pub [u32; 16]);
+impl Tile8bpp {
+  /// Gets the pixel at `(x, y)`.
+  ///
+  /// # Panics
+  ///
+  /// If `x` or `y` are 8 or more.
+  pub fn pixel(&self, x: usize, y: usize) -> u8 {
+    assert!(x < 8, "x out of range");
+    (self.0[y * 2 + x / 4] >> ((x % 4) * 8)) as u8
+  }
+
+  /// Sets the pixel at `(x, y)`.
+  ///
+  /// # Panics
+  ///
+  /// If `x` or `y` are 8 or more.
+  pub fn set_pixel(&mut self, x: usize, y: usize, value: u8) {
+    assert!(x < 8, "x out of range");
+    let word = &mut self.0[y * 2 + x / 4];
+    let shift = (x % 4) * 8;
+    *word = (*word & !(0xFF << shift)) | (u32::from(value) << shift);
+  }
+}
+
 newtype! {
   /// Allows control of the Mosaic effect.
   ///
@@ -189,6 +289,269 @@ newtype! {
   FP_I_19_8, pub i32
 }
 
+macro_rules! impl_fixed_point {
+  ($t:ident, $raw:ident, $wide:ident) => {
+    impl $t {
+      /// The number of fractional bits.
+      pub const FRACTION_BITS: u32 = 8;
+
+      /// The value 1.0
+      pub const ONE: Self = $t(1 << 8);
+
+      /// The smallest value.
+      pub const MIN: Self = $t($raw::min_value());
+
+      /// The largest value.
+      pub const MAX: Self = $t($raw::max_value());
+
+      /// Makes a value from the raw bits.
+      pub const fn from_raw(raw: $raw) -> Self {
+        $t(raw)
+      }
+
+      /// Makes a value from an integer. Any integral bits that don't fit are
+      /// lost.
+      pub const fn from_int(int: $raw) -> Self {
+        $t(int << 8)
+      }
+
+      /// The raw bits of the value.
+      pub const fn to_raw(self) -> $raw {
+        self.0
+      }
+
+      /// The integral part of the value, rounding towards negative infinity.
+      pub const fn to_int(self) -> $raw {
+        self.0 >> 8
+      }
+
+      /// The fractional part of the value, as a count of 256ths.
+      pub const fn fract(self) -> $raw {
+        self.0 & 0xFF
+      }
+
+      /// The product at the wider size, rounded to the nearest 256th.
+      const fn mul_wide(self, rhs: Self) -> $wide {
+        ((self.0 as $wide) * (rhs.0 as $wide) + 0x80) >> 8
+      }
+
+      /// The quotient at the wider size, rounded to the nearest 256th (with
+      /// halves going away from zero).
+      fn div_wide(self, rhs: Self) -> $wide {
+        let num = (self.0 as $wide) << 8;
+        let den = rhs.0 as $wide;
+        if (num < 0) == (den < 0) {
+          (num + den / 2) / den
+        } else {
+          (num - den / 2) / den
+        }
+      }
+
+      /// Clamps a wide value into range.
+      fn saturate(wide: $wide) -> Self {
+        if wide < ($raw::min_value() as $wide) {
+          Self::MIN
+        } else if wide > ($raw::max_value() as $wide) {
+          Self::MAX
+        } else {
+          $t(wide as $raw)
+        }
+      }
+
+      /// Addition that wraps on overflow.
+      pub const fn wrapping_add(self, rhs: Self) -> Self {
+        $t(self.0.wrapping_add(rhs.0))
+      }
+
+      /// Subtraction that wraps on overflow.
+      pub const fn wrapping_sub(self, rhs: Self) -> Self {
+        $t(self.0.wrapping_sub(rhs.0))
+      }
+
+      /// Multiplication that wraps on overflow.
+      pub const fn wrapping_mul(self, rhs: Self) -> Self {
+        $t(self.mul_wide(rhs) as $raw)
+      }
+
+      /// Division that wraps on overflow.
+      ///
+      /// # Panics
+      ///
+      /// If `rhs` is zero.
+      pub fn wrapping_div(self, rhs: Self) -> Self {
+        $t(self.div_wide(rhs) as $raw)
+      }
+
+      /// Negation that wraps on overflow (only `MIN` overflows).
+      pub const fn wrapping_neg(self) -> Self {
+        $t(self.0.wrapping_neg())
+      }
+
+      /// Addition that clamps to `MIN` or `MAX` on overflow.
+      pub fn saturating_add(self, rhs: Self) -> Self {
+        $t(self.0.saturating_add(rhs.0))
+      }
+
+      /// Subtraction that clamps to `MIN` or `MAX` on overflow.
+      pub fn saturating_sub(self, rhs: Self) -> Self {
+        $t(self.0.saturating_sub(rhs.0))
+      }
+
+      /// Multiplication that clamps to `MIN` or `MAX` on overflow.
+      pub fn saturating_mul(self, rhs: Self) -> Self {
+        Self::saturate(self.mul_wide(rhs))
+      }
+
+      /// Division that clamps to `MIN` or `MAX` on overflow.
+      ///
+      /// # Panics
+      ///
+      /// If `rhs` is zero.
+      pub fn saturating_div(self, rhs: Self) -> Self {
+        Self::saturate(self.div_wide(rhs))
+      }
+
+      /// Negation that clamps to `MAX` on overflow (only `MIN` overflows).
+      pub fn saturating_neg(self) -> Self {
+        $t(self.0.saturating_neg())
+      }
+
+      /// Converts from an `f32`, rounding to the nearest 256th and clamping to
+      /// `MIN` or `MAX`.
+      #[cfg(feature = "float")]
+      pub fn from_f32(f: f32) -> Self {
+        let scaled = f * 256.0;
+        $t((if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 }) as $raw)
+      }
+
+      /// Converts to an `f32`.
+      #[cfg(feature = "float")]
+      pub fn to_f32(self) -> f32 {
+        self.0 as f32 / 256.0
+      }
+    }
+    impl core::ops::Add for $t {
+      type Output = Self;
+      fn add(self, rhs: Self) -> Self {
+        $t(self.0 + rhs.0)
+      }
+    }
+    impl core::ops::Sub for $t {
+      type Output = Self;
+      fn sub(self, rhs: Self) -> Self {
+        $t(self.0 - rhs.0)
+      }
+    }
+    impl core::ops::Mul for $t {
+      type Output = Self;
+      /// Rounds to the nearest 256th.
+      ///
+      /// # Panics
+      ///
+      /// In debug builds, if the product is out of range.
+      fn mul(self, rhs: Self) -> Self {
+        let wide = self.mul_wide(rhs);
+        debug_assert!(
+          wide >= ($raw::min_value() as $wide) && wide <= ($raw::max_value() as $wide),
+          "attempt to multiply with overflow"
+        );
+        $t(wide as $raw)
+      }
+    }
+    impl core::ops::Div for $t {
+      type Output = Self;
+      /// Rounds to the nearest 256th, with halves going away from zero.
+      ///
+      /// # Panics
+      ///
+      /// If `rhs` is zero, or in debug builds, if the quotient is out of range.
+      fn div(self, rhs: Self) -> Self {
+        let wide = self.div_wide(rhs);
+        debug_assert!(
+          wide >= ($raw::min_value() as $wide) && wide <= ($raw::max_value() as $wide),
+          "attempt to divide with overflow"
+        );
+        $t(wide as $raw)
+      }
+    }
+    impl core::ops::Neg for $t {
+      type Output = Self;
+      /// # Panics
+      ///
+      /// In debug builds, if the value is `MIN`.
+      fn neg(self) -> Self {
+        $t(-self.0)
+      }
+    }
+    impl core::ops::AddAssign for $t {
+      fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+      }
+    }
+    impl core::ops::SubAssign for $t {
+      fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+      }
+    }
+    impl core::ops::MulAssign for $t {
+      fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+      }
+    }
+    impl core::ops::DivAssign for $t {
+      fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs
+      }
+    }
+    impl PartialOrd for $t {
+      fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+      }
+    }
+    impl Ord for $t {
+      fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.0.cmp(&other.0)
+      }
+    }
+  };
+}
+impl_fixed_point!(FP_I_7_8, i16, i32);
+impl_fixed_point!(FP_I_19_8, i32, i64);
+
+impl FP_I_7_8 {
+  /// Converts to the wider type, which is always exact.
+  pub const fn to_fp_i_19_8(self) -> FP_I_19_8 {
+    FP_I_19_8(self.0 as i32)
+  }
+}
+impl From<FP_I_7_8> for FP_I_19_8 {
+  fn from(fp: FP_I_7_8) -> Self {
+    fp.to_fp_i_19_8()
+  }
+}
+
+impl FP_I_19_8 {
+  /// Converts to the narrower type, if the value fits.
+  pub fn checked_to_fp_i_7_8(self) -> Option<FP_I_7_8> {
+    if self.0 >= i32::from(i16::min_value()) && self.0 <= i32::from(i16::max_value()) {
+      Some(FP_I_7_8(self.0 as i16))
+    } else {
+      None
+    }
+  }
+
+  /// Converts to the narrower type, clamping to `MIN` or `MAX` if the value
+  /// doesn't fit.
+  pub fn saturating_to_fp_i_7_8(self) -> FP_I_7_8 {
+    FP_I_7_8::saturate(self.0)
+  }
+
+  /// Converts to the narrower type, keeping only the low 16 bits.
+  pub const fn wrapping_to_fp_i_7_8(self) -> FP_I_7_8 {
+    FP_I_7_8(self.0 as i16)
+  }
+}
+
 newtype! {
   /// Horizontal control for Window effect.
   ///
@@ -669,8 +1032,11 @@ newtype! {
 impl SoundStatusMaster {
   phantom_fields! {
     self.0: u16,
-    bias_level: 1-9,
-    amplitude_resolution: 14-15,
+    pulse_a_active: 0,
+    pulse_b_active: 1,
+    wave_active: 2,
+    noise_active: 3,
+    sound_enabled: 7,
   }
 }
 
@@ -783,6 +1149,208 @@ impl TimerControl {
   }
 }
 
+newtype_enum! {
+  /// Which of the four serial modes the link port is using.
+  ///
+  /// These are the SIOCNT bits 12-13, and they only apply when `RCNT` is also
+  /// set to one of the serial modes.
+  SioMode = u16,
+  /// Normal mode, 8-bit transfers.
+  Normal8bit = 0,
+  /// Normal mode, 32-bit transfers.
+  Normal32bit = 1,
+  /// Multi-player mode (up to 4 GBAs).
+  MultiPlayer = 2,
+  /// UART mode (RS232 style).
+  Uart = 3,
+}
+
+newtype_enum! {
+  /// Baud rate for Multi-player and UART modes.
+  SioBaudRate = u16,
+  /// 9600 bps
+  B9600 = 0,
+  /// 38400 bps
+  B38400 = 1,
+  /// 57600 bps
+  B57600 = 2,
+  /// 115200 bps
+  B115200 = 3,
+}
+
+newtype! {
+  /// Serial control while in Normal mode (8-bit or 32-bit). GBATEK `SIOCNT`.
+  ///
+  /// * 0: Use the internal shift clock (otherwise external)
+  /// * 1: Internal shift clock is 2MHz (otherwise 256KHz)
+  /// * 2 (ro): SI state (the other side's SO)
+  /// * 3: SO state during inactivity
+  /// * 7: Start bit. Set to begin a transfer, it clears when done.
+  /// * 12-13: `SioMode`, should be `Normal8bit` or `Normal32bit` here.
+  /// * 14: Interrupt when the transfer completes
+  SioNormalControl, u16
+}
+#[allow(missing_docs)]
+impl SioNormalControl {
+  phantom_fields! {
+    self.0: u16,
+    internal_clock: 0,
+    clock_2mhz: 1,
+    si_state: 2,
+    so_inactive: 3,
+    start: 7,
+    mode: 12-13=SioMode<Normal8bit, Normal32bit, MultiPlayer, Uart>,
+    irq_enable: 14,
+  }
+}
+
+newtype! {
+  /// Serial control while in Multi-player mode. GBATEK `SIOCNT`.
+  ///
+  /// * 0-1: `SioBaudRate`
+  /// * 2 (ro): SI terminal, `false` for the parent and `true` for a child
+  /// * 3 (ro): SD terminal, `true` when all GBAs are ready
+  /// * 4-5 (ro): Multi-player ID. 0 is the parent, 1-3 are the children.
+  /// * 6 (ro): Error flag
+  /// * 7: Start / Busy bit. Only the parent can start a transfer.
+  /// * 12-13: `SioMode`, should be `MultiPlayer` here.
+  /// * 14: Interrupt when the transfer completes
+  SioMultiControl, u16
+}
+#[allow(missing_docs)]
+impl SioMultiControl {
+  phantom_fields! {
+    self.0: u16,
+    baud_rate: 0-1=SioBaudRate<B9600, B38400, B57600, B115200>,
+    is_child: 2,
+    all_ready: 3,
+    multi_id: 4-5,
+    error: 6,
+    busy: 7,
+    mode: 12-13=SioMode<Normal8bit, Normal32bit, MultiPlayer, Uart>,
+    irq_enable: 14,
+  }
+}
+
+newtype! {
+  /// Serial control while in UART mode. GBATEK `SIOCNT`.
+  ///
+  /// * 0-1: `SioBaudRate`
+  /// * 2: CTS flag, only send while the other side's SC is low
+  /// * 3: Parity is odd (otherwise even)
+  /// * 4 (ro): Send data is full
+  /// * 5 (ro): Receive data is empty
+  /// * 6 (ro): Error flag
+  /// * 7: Data length is 8 bits (otherwise 7 bits)
+  /// * 8: FIFO enable
+  /// * 9: Parity enable
+  /// * 10: Send enable
+  /// * 11: Receive enable
+  /// * 12-13: `SioMode`, should be `Uart` here.
+  /// * 14: Interrupt on send/receive (or on error)
+  SioUartControl, u16
+}
+#[allow(missing_docs)]
+impl SioUartControl {
+  phantom_fields! {
+    self.0: u16,
+    baud_rate: 0-1=SioBaudRate<B9600, B38400, B57600, B115200>,
+    cts: 2,
+    odd_parity: 3,
+    send_full: 4,
+    receive_empty: 5,
+    error: 6,
+    data_8bit: 7,
+    fifo_enable: 8,
+    parity_enable: 9,
+    send_enable: 10,
+    receive_enable: 11,
+    mode: 12-13=SioMode<Normal8bit, Normal32bit, MultiPlayer, Uart>,
+    irq_enable: 14,
+  }
+}
+
+newtype_enum! {
+  /// The overall mode of the link port (`RCNT` bits 14-15).
+  LinkPortMode = u16,
+  /// One of the serial modes, as selected by `SIOCNT`.
+  Serial = 0,
+  /// The hardware treats this the same as `Serial`.
+  SerialAlt = 1,
+  /// The four link port pins are driven directly.
+  GeneralPurpose = 2,
+  /// JOY Bus mode (GameCube link).
+  JoyBus = 3,
+}
+
+newtype! {
+  /// Link port mode selection. GBATEK `RCNT`.
+  ///
+  /// The data and direction bits are only used in General Purpose mode.
+  ///
+  /// * 0-3: SC, SD, SI, and SO data bits
+  /// * 4-7: SC, SD, SI, and SO are outputs (otherwise inputs)
+  /// * 8: Interrupt when SI falls
+  /// * 14-15: `LinkPortMode`
+  LinkPortControl, u16
+}
+#[allow(missing_docs)]
+impl LinkPortControl {
+  phantom_fields! {
+    self.0: u16,
+    sc_data: 0,
+    sd_data: 1,
+    si_data: 2,
+    so_data: 3,
+    sc_output: 4,
+    sd_output: 5,
+    si_output: 6,
+    so_output: 7,
+    si_irq: 8,
+    mode: 14-15=LinkPortMode<Serial, SerialAlt, GeneralPurpose, JoyBus>,
+  }
+}
+
+newtype! {
+  /// JOY Bus control. GBATEK `JOYCNT`.
+  ///
+  /// The three flags are "write 1 to clear", like with `IRQ_ACKNOWLEDGE`.
+  ///
+  /// * 0: Device reset command received
+  /// * 1: Receive complete
+  /// * 2: Send complete
+  /// * 6: Interrupt when a device reset command is received
+  JoyBusControl, u16
+}
+#[allow(missing_docs)]
+impl JoyBusControl {
+  phantom_fields! {
+    self.0: u16,
+    device_reset: 0,
+    receive_complete: 1,
+    send_complete: 2,
+    reset_irq: 6,
+  }
+}
+
+newtype! {
+  /// JOY Bus status. GBATEK `JOYSTAT`.
+  ///
+  /// * 1 (ro): Receive status, `false` when the remote side is/was receiving
+  /// * 3 (ro): Send status, `true` when the remote side is/was sending
+  /// * 4-5: General purpose flags, not used by the hardware.
+  JoyBusStatus, u16
+}
+#[allow(missing_docs)]
+impl JoyBusStatus {
+  phantom_fields! {
+    self.0: u16,
+    receive: 1,
+    send: 3,
+    general_purpose: 4-5,
+  }
+}
+
 newtype! {
   /// This is the GBA's _native_ key reading value.
   ///
@@ -799,9 +1367,7 @@ newtype! {
   /// let high_active_keys_value = key_input.0 ^ 0b11_1111_1111;
   /// ```
   ///
-  /// That's not provided by this crate because this crate sticks to minimal
-  /// declarations. You can of course use the [gba](https://docs.rs/gba) crate
-  /// for this and other helpful higher level abstractions.
+  /// The [Keys] type does this for you, with `Keys::from(key_input)`.
   ///
   /// The bits are as follows:
   ///
@@ -834,6 +1400,94 @@ impl KeyInput {
   }
 }
 
+newtype! {
+  /// High-active key values, where `true` is "pressed".
+  ///
+  /// This is [KeyInput] with the lower 10 bits flipped, and uses the same bit
+  /// for each button.
+  Keys, pub u16
+}
+#[allow(missing_docs)]
+impl Keys {
+  phantom_fields! {
+    self.0: u16,
+    a: 0,
+    b: 1,
+    select: 2,
+    start: 3,
+    right: 4,
+    left: 5,
+    up: 6,
+    down: 7,
+    r: 8,
+    l: 9,
+  }
+}
+impl Keys {
+  /// Every button.
+  pub const ALL: Self = Keys(0b11_1111_1111);
+
+  /// Converts from the low-active key values.
+  pub const fn from_key_input(input: KeyInput) -> Self {
+    Keys((input.0 ^ 0b11_1111_1111) & 0b11_1111_1111)
+  }
+
+  /// Converts to the low-active key values.
+  pub const fn to_key_input(self) -> KeyInput {
+    KeyInput((self.0 ^ 0b11_1111_1111) & 0b11_1111_1111)
+  }
+
+  /// If no buttons are pressed.
+  pub const fn is_empty(self) -> bool {
+    self.0 & 0b11_1111_1111 == 0
+  }
+
+  /// If every button in `other` is also pressed in `self`.
+  pub const fn contains(self, other: Self) -> bool {
+    self.0 & other.0 == other.0
+  }
+
+  /// The d-pad left/right value: -1 for left, 1 for right, or 0 for both or
+  /// neither.
+  pub const fn x_axis(self) -> i8 {
+    (self.right() as i8) - (self.left() as i8)
+  }
+
+  /// The d-pad up/down value: -1 for up, 1 for down, or 0 for both or
+  /// neither. This matches screen coordinates, where y goes down.
+  pub const fn y_axis(self) -> i8 {
+    (self.down() as i8) - (self.up() as i8)
+  }
+}
+impl From<KeyInput> for Keys {
+  fn from(input: KeyInput) -> Self {
+    Self::from_key_input(input)
+  }
+}
+impl From<Keys> for KeyInput {
+  fn from(keys: Keys) -> Self {
+    keys.to_key_input()
+  }
+}
+impl core::ops::BitAnd for Keys {
+  type Output = Self;
+  fn bitand(self, rhs: Self) -> Self {
+    Keys(self.0 & rhs.0)
+  }
+}
+impl core::ops::BitOr for Keys {
+  type Output = Self;
+  fn bitor(self, rhs: Self) -> Self {
+    Keys(self.0 | rhs.0)
+  }
+}
+impl core::ops::Not for Keys {
+  type Output = Self;
+  fn not(self) -> Self {
+    Keys(!self.0 & 0b11_1111_1111)
+  }
+}
+
 newtype! {
   /// Affects when a key interrupt is triggered.
   ///
@@ -914,6 +1568,17 @@ impl InterruptEnable {
     game_pak: 13,
   }
 }
+impl InterruptEnable {
+  /// Makes a value from raw bits, ignoring bits 14 and 15.
+  pub const fn from_bits(bits: u16) -> Self {
+    InterruptEnable(bits & 0x3FFF)
+  }
+
+  /// The raw bits.
+  pub const fn bits(self) -> u16 {
+    self.0
+  }
+}
 
 newtype! {
   /// Interrupt Requested Flags. GBATEK `IF` register.
@@ -951,6 +1616,210 @@ impl InterruptRequestFlags {
     game_pak: 13,
   }
 }
+impl InterruptRequestFlags {
+  /// Makes a value from raw bits, ignoring bits 14 and 15.
+  pub const fn from_bits(bits: u16) -> Self {
+    InterruptRequestFlags(bits & 0x3FFF)
+  }
+
+  /// The raw bits.
+  pub const fn bits(self) -> u16 {
+    self.0
+  }
+}
+
+newtype_enum! {
+  /// One of the interrupt sources, numbered by its bit in `IE` and `IF`.
+  ///
+  /// The lower numbers have the higher priority.
+  Interrupt = u16,
+  /// VBlank
+  VBlank = 0,
+  /// HBlank
+  HBlank = 1,
+  /// VCounter match
+  VCounter = 2,
+  /// Timer 0 overflow
+  Timer0 = 3,
+  /// Timer 1 overflow
+  Timer1 = 4,
+  /// Timer 2 overflow
+  Timer2 = 5,
+  /// Timer 3 overflow
+  Timer3 = 6,
+  /// Serial communication
+  Serial = 7,
+  /// DMA 0 completed
+  Dma0 = 8,
+  /// DMA 1 completed
+  Dma1 = 9,
+  /// DMA 2 completed
+  Dma2 = 10,
+  /// DMA 3 completed
+  Dma3 = 11,
+  /// Keypad
+  Keypad = 12,
+  /// Game Pak
+  GamePak = 13,
+}
+impl Interrupt {
+  /// Every interrupt, in priority order.
+  pub const ALL: [Interrupt; 14] = [
+    Interrupt::VBlank,
+    Interrupt::HBlank,
+    Interrupt::VCounter,
+    Interrupt::Timer0,
+    Interrupt::Timer1,
+    Interrupt::Timer2,
+    Interrupt::Timer3,
+    Interrupt::Serial,
+    Interrupt::Dma0,
+    Interrupt::Dma1,
+    Interrupt::Dma2,
+    Interrupt::Dma3,
+    Interrupt::Keypad,
+    Interrupt::GamePak,
+  ];
+
+  /// The interrupt's bit in `IE` and `IF`.
+  pub const fn mask(self) -> u16 {
+    1 << (self as u16)
+  }
+}
+
+newtype_enum! {
+  /// Wait cycles for a first (non-sequential) game pak access.
+  ///
+  /// Also used for the SRAM wait, which only has a single access type.
+  FirstAccessWait = u16,
+  /// 4 cycles
+  Cycles4 = 0,
+  /// 3 cycles
+  Cycles3 = 1,
+  /// 2 cycles
+  Cycles2 = 2,
+  /// 8 cycles
+  Cycles8 = 3,
+}
+
+newtype_enum! {
+  /// Wait cycles for a second (sequential) Wait State 0 access.
+  Ws0SecondAccess = u16,
+  /// 2 cycles
+  Cycles2 = 0,
+  /// 1 cycle
+  Cycles1 = 1,
+}
+
+newtype_enum! {
+  /// Wait cycles for a second (sequential) Wait State 1 access.
+  Ws1SecondAccess = u16,
+  /// 4 cycles
+  Cycles4 = 0,
+  /// 1 cycle
+  Cycles1 = 1,
+}
+
+newtype_enum! {
+  /// Wait cycles for a second (sequential) Wait State 2 access.
+  Ws2SecondAccess = u16,
+  /// 8 cycles
+  Cycles8 = 0,
+  /// 1 cycle
+  Cycles1 = 1,
+}
+
+newtype_enum! {
+  /// The clock output on the game pak PHI terminal.
+  ///
+  /// Nothing normally uses this, so leave it disabled to save power.
+  PhiTerminalOutput = u16,
+  /// No output
+  Disabled = 0,
+  /// 4.19 MHz
+  MHz4 = 1,
+  /// 8.38 MHz
+  MHz8 = 2,
+  /// 16.78 MHz
+  MHz16 = 3,
+}
+
+newtype! {
+  /// Game pak waitstate control. GBATEK `WAITCNT`.
+  ///
+  /// The game pak ROM is mirrored three times, at `0x800_0000` (Wait State 0),
+  /// `0xA00_0000` (Wait State 1), and `0xC00_0000` (Wait State 2), and each
+  /// mirror gets its own timings. Most carts are fine with WS0 at 3/1 and the
+  /// prefetch buffer on, but check what your cart (or flash cart) supports.
+  ///
+  /// * 0-1: SRAM wait (`FirstAccessWait`)
+  /// * 2-3: WS0 first access
+  /// * 4: WS0 second access
+  /// * 5-6: WS1 first access
+  /// * 7: WS1 second access
+  /// * 8-9: WS2 first access
+  /// * 10: WS2 second access
+  /// * 11-12: PHI terminal output
+  /// * 14: Game pak prefetch buffer enable
+  /// * 15 (ro): Game pak type, `true` for a CGB cart
+  WaitstateControl, u16
+}
+#[allow(missing_docs)]
+impl WaitstateControl {
+  phantom_fields! {
+    self.0: u16,
+    sram_wait: 0-1=FirstAccessWait<Cycles4, Cycles3, Cycles2, Cycles8>,
+    ws0_first_access: 2-3=FirstAccessWait<Cycles4, Cycles3, Cycles2, Cycles8>,
+    ws0_second_access: 4-4=Ws0SecondAccess<Cycles2, Cycles1>,
+    ws1_first_access: 5-6=FirstAccessWait<Cycles4, Cycles3, Cycles2, Cycles8>,
+    ws1_second_access: 7-7=Ws1SecondAccess<Cycles4, Cycles1>,
+    ws2_first_access: 8-9=FirstAccessWait<Cycles4, Cycles3, Cycles2, Cycles8>,
+    ws2_second_access: 10-10=Ws2SecondAccess<Cycles8, Cycles1>,
+    phi_terminal_output: 11-12=PhiTerminalOutput<Disabled, MHz4, MHz8, MHz16>,
+    prefetch_buffer: 14,
+    game_pak_is_cgb: 15,
+  }
+}
+
+newtype! {
+  /// Low power mode selection. GBATEK `HALTCNT`.
+  ///
+  /// * 7: Stop mode (otherwise Halt mode)
+  ///
+  /// In Halt mode the CPU sleeps until an interrupt is requested (`IE` and
+  /// `IF` overlap). In Stop mode almost everything is switched off and only a
+  /// keypad, game pak, or serial interrupt will wake it again.
+  HaltControl, u8
+}
+#[allow(missing_docs)]
+impl HaltControl {
+  phantom_fields! {
+    self.0: u8,
+    stop: 7,
+  }
+}
+
+newtype! {
+  /// Undocumented internal memory control.
+  ///
+  /// * 0: Disable both IWRAM and EWRAM (crashes the program, don't)
+  /// * 5: EWRAM enable
+  /// * 24-27: EWRAM wait control, `15 - n` wait cycles. The BIOS sets 13 (2
+  ///   waits), which is the slowest that any hardware requires. Some units are
+  ///   stable at 14 (1 wait), and the GBA Micro locks up with it.
+  ///
+  /// The value set by the BIOS on boot is `0x0D00_0020`.
+  InternalMemoryControl, u32
+}
+#[allow(missing_docs)]
+impl InternalMemoryControl {
+  phantom_fields! {
+    self.0: u32,
+    disable_wram: 0,
+    ewram_enable: 5,
+    ewram_wait_control: 24-27,
+  }
+}
 
 newtype! {
   /// Color values on the GBA.
@@ -965,6 +1834,167 @@ impl Color {
     blue: 10-14,
   }
 }
+impl Color {
+  /// Black
+  pub const BLACK: Self = Self::rgb(0, 0, 0);
+  /// White
+  pub const WHITE: Self = Self::rgb(31, 31, 31);
+  /// Red
+  pub const RED: Self = Self::rgb(31, 0, 0);
+  /// Green
+  pub const GREEN: Self = Self::rgb(0, 31, 0);
+  /// Blue
+  pub const BLUE: Self = Self::rgb(0, 0, 31);
+  /// Yellow
+  pub const YELLOW: Self = Self::rgb(31, 31, 0);
+  /// Cyan
+  pub const CYAN: Self = Self::rgb(0, 31, 31);
+  /// Magenta
+  pub const MAGENTA: Self = Self::rgb(31, 0, 31);
+
+  /// Makes a color from 5-bit channels (0 to 31). Extra bits are ignored.
+  pub const fn rgb(red: u16, green: u16, blue: u16) -> Self {
+    Color((red & 31) | ((green & 31) << 5) | ((blue & 31) << 10))
+  }
+
+  /// Makes a color from 8-bit channels, rounding to the nearest 5-bit value.
+  pub const fn from_rgb888(red: u8, green: u8, blue: u8) -> Self {
+    Self::rgb(channel_from_8bit(red), channel_from_8bit(green), channel_from_8bit(blue))
+  }
+
+  /// The color with 8-bit channels, as `(red, green, blue)`.
+  ///
+  /// The high bits of each channel are repeated in the low bits, so that 31
+  /// becomes 255.
+  pub const fn to_rgb888(self) -> (u8, u8, u8) {
+    (channel_to_8bit(self.red()), channel_to_8bit(self.green()), channel_to_8bit(self.blue()))
+  }
+
+  /// Makes a color from 8-bit channels as they should appear on the GBA's
+  /// screen.
+  ///
+  /// This is the inverse of [to_rgb888_lcd](Color::to_rgb888_lcd), picking the
+  /// nearest color.
+  pub const fn from_rgb888_lcd(red: u8, green: u8, blue: u8) -> Self {
+    Self::rgb(channel_from_lcd(red), channel_from_lcd(green), channel_from_lcd(blue))
+  }
+
+  /// The color with 8-bit channels, as it appears on the GBA's screen.
+  ///
+  /// The original GBA screen is much darker than a modern display. This uses
+  /// a gamma of 4.0 for the GBA screen and 2.2 for the display, per channel
+  /// (it doesn't model the slight bleeding between channels).
+  pub const fn to_rgb888_lcd(self) -> (u8, u8, u8) {
+    (
+      LCD_GAMMA[self.red() as usize],
+      LCD_GAMMA[self.green() as usize],
+      LCD_GAMMA[self.blue() as usize],
+    )
+  }
+
+  /// Linear interpolation from this color towards another color.
+  ///
+  /// The position `t` is x/256, and values above 256 are capped at 256/256
+  /// (which gives `other`). Each channel is rounded to the nearest value.
+  pub const fn lerp(self, other: Self, t: u16) -> Self {
+    let t = if t > 256 { 256 } else { t };
+    Self::rgb(
+      lerp_channel(self.red(), other.red(), t),
+      lerp_channel(self.green(), other.green(), t),
+      lerp_channel(self.blue(), other.blue(), t),
+    )
+  }
+
+  /// The result of the hardware alpha blend (see [BlendAlpha]).
+  ///
+  /// `self` is the 1st target pixel and `second` is the 2nd target pixel.
+  pub const fn blend_alpha(self, second: Self, blend: BlendAlpha) -> Self {
+    let eva = cap_coefficient(blend.eva_coefficient());
+    let evb = cap_coefficient(blend.evb_coefficient());
+    Self::rgb(
+      alpha_channel(self.red(), second.red(), eva, evb),
+      alpha_channel(self.green(), second.green(), eva, evb),
+      alpha_channel(self.blue(), second.blue(), eva, evb),
+    )
+  }
+
+  /// The result of the hardware brightness increase (see [BlendBrightness]).
+  pub const fn blend_brightness_increase(self, blend: BlendBrightness) -> Self {
+    let evy = cap_coefficient(blend.evy_coefficient());
+    Self::rgb(
+      self.red() + (((31 - self.red()) * evy) >> 4),
+      self.green() + (((31 - self.green()) * evy) >> 4),
+      self.blue() + (((31 - self.blue()) * evy) >> 4),
+    )
+  }
+
+  /// The result of the hardware brightness decrease (see [BlendBrightness]).
+  pub const fn blend_brightness_decrease(self, blend: BlendBrightness) -> Self {
+    let evy = cap_coefficient(blend.evy_coefficient());
+    Self::rgb(
+      self.red() - ((self.red() * evy) >> 4),
+      self.green() - ((self.green() * evy) >> 4),
+      self.blue() - ((self.blue() * evy) >> 4),
+    )
+  }
+}
+
+/// Each 5-bit channel value as it appears on the GBA's screen, in 8-bit.
+const LCD_GAMMA: [u8; 32] = [
+  0, 0, 2, 4, 6, 9, 13, 17, 22, 27, 33, 39, 45, 53, 60, 68, 77, 86, 95, 105, 115, 126, 137, 148,
+  160, 172, 185, 198, 212, 226, 240, 255,
+];
+
+const fn channel_from_8bit(c: u8) -> u16 {
+  ((c as u16) * 31 + 127) / 255
+}
+
+const fn channel_to_8bit(c: u16) -> u8 {
+  ((c << 3) | (c >> 2)) as u8
+}
+
+const fn channel_from_lcd(c: u8) -> u16 {
+  let mut best = 0;
+  let mut i = 1;
+  while i < 32 {
+    if abs_diff(LCD_GAMMA[i], c) < abs_diff(LCD_GAMMA[best], c) {
+      best = i;
+    }
+    i += 1;
+  }
+  best as u16
+}
+
+const fn abs_diff(a: u8, b: u8) -> u8 {
+  if a > b {
+    a - b
+  } else {
+    b - a
+  }
+}
+
+const fn lerp_channel(a: u16, b: u16, t: u16) -> u16 {
+  let (a, b, t) = (a as i32, b as i32, t as i32);
+  (a + (((b - a) * t + 128) >> 8)) as u16
+}
+
+const fn alpha_channel(first: u16, second: u16, eva: u16, evb: u16) -> u16 {
+  let sum = (first * eva + second * evb) >> 4;
+  if sum > 31 {
+    31
+  } else {
+    sum
+  }
+}
+
+/// Blend coefficients above 16 act as 16.
+const fn cap_coefficient(ev: u16) -> u16 {
+  if ev > 16 {
+    16
+  } else {
+    ev
+  }
+}
 
 newtype! {
   /// 0th part of an object's attributes.
@@ -1077,6 +2107,72 @@ pub enum ObjectSize {
   Three = 3,
 }
 
+/// The size of an object in tiles, as `(width, height)`.
+pub const fn obj_size_tiles(shape: ObjectShape, size: ObjectSize) -> (u16, u16) {
+  match (shape, size) {
+    (ObjectShape::Square, ObjectSize::Zero) => (1, 1),
+    (ObjectShape::Square, ObjectSize::One) => (2, 2),
+    (ObjectShape::Square, ObjectSize::Two) => (4, 4),
+    (ObjectShape::Square, ObjectSize::Three) => (8, 8),
+    (ObjectShape::Horizontal, ObjectSize::Zero) => (2, 1),
+    (ObjectShape::Horizontal, ObjectSize::One) => (4, 1),
+    (ObjectShape::Horizontal, ObjectSize::Two) => (4, 2),
+    (ObjectShape::Horizontal, ObjectSize::Three) => (8, 4),
+    (ObjectShape::Vertical, ObjectSize::Zero) => (1, 2),
+    (ObjectShape::Vertical, ObjectSize::One) => (1, 4),
+    (ObjectShape::Vertical, ObjectSize::Two) => (2, 4),
+    (ObjectShape::Vertical, ObjectSize::Three) => (4, 8),
+  }
+}
+
+/// The size of an object in pixels, as `(width, height)`.
+pub const fn obj_size_pixels(shape: ObjectShape, size: ObjectSize) -> (u16, u16) {
+  let (width, height) = obj_size_tiles(shape, size);
+  (width * 8, height * 8)
+}
+
+/// How many tile indexes one frame of an object uses.
+///
+/// Tile indexes always count in 32 byte steps, so each 8bpp tile takes up two
+/// indexes.
+pub const fn obj_tile_count(shape: ObjectShape, size: ObjectSize, is_8bpp: bool) -> u16 {
+  let (width, height) = obj_size_tiles(shape, size);
+  width * height * if is_8bpp { 2 } else { 1 }
+}
+
+/// The tile index of one tile within an object.
+///
+/// The object's `OBJAttr2::tile_id` is `base_tile`, and this gives the index
+/// of the tile at `(tile_x, tile_y)` within the object, which depends on the
+/// object tile mapping (`DisplayControl::oam_memory_1d`):
+///
+/// * 1D: The object's tiles follow each other in row-major order.
+/// * 2D: Object tile memory is treated as a 32x32 grid of tile indexes, and
+///   each row of the object's tiles is in the next row of the grid.
+pub const fn obj_tile_id(
+  dispcnt: DisplayControl, base_tile: u16, width_tiles: u16, tile_x: u16, tile_y: u16,
+  is_8bpp: bool,
+) -> u16 {
+  let step = if is_8bpp { 2 } else { 1 };
+  let offset = if dispcnt.oam_memory_1d() {
+    (tile_y * width_tiles + tile_x) * step
+  } else {
+    tile_y * 32 + tile_x * step
+  };
+  base_tile.wrapping_add(offset) & 0x3FF
+}
+
+/// The range of object tile indexes that can be used in a display mode.
+///
+/// In the bitmap modes (3, 4, and 5) the bitmap uses the lower half of object
+/// tile memory, so only tiles 512 and up can be used.
+pub const fn obj_tile_range(mode: DisplayMode) -> core::ops::Range<u16> {
+  match mode {
+    DisplayMode::Mode0 | DisplayMode::Mode1 | DisplayMode::Mode2 => 0..1024,
+    DisplayMode::Mode3 | DisplayMode::Mode4 | DisplayMode::Mode5 => 512..1024,
+  }
+}
+
 newtype! {
   /// 2nd part of an object's attributes.
   ///
@@ -1094,3 +2190,517 @@ impl OBJAttr2 {
     palbank: 12-15,
   }
 }
+
+/// A full object attribute entry, laid out the same as in OAM.
+///
+/// OAM interleaves the affine parameters with the object entries, so the
+/// `padding` of each group of four entries holds one affine parameter set
+/// (`pa` in the first entry, then `pb`, `pc`, and `pd`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub struct ObjAttributes {
+  /// Attribute 0
+  pub attr0: OBJAttr0,
+  /// Attribute 1
+  pub attr1: OBJAttr1,
+  /// Attribute 2
+  pub attr2: OBJAttr2,
+  /// Part of an affine parameter set, not part of the object.
+  pub padding: i16,
+}
+impl ObjAttributes {
+  /// An entry that isn't displayed.
+  pub const HIDDEN: Self = Self {
+    attr0: OBJAttr0::new().with_obj_rendering(ObjectRender::Disabled),
+    attr1: OBJAttr1::new(),
+    attr2: OBJAttr2::new(),
+    padding: 0,
+  };
+}
+
+/// The compressed Nintendo logo bitmap that must be in every cartridge header.
+///
+/// The BIOS checks this during boot, and won't run the cart if it's wrong.
+pub const NINTENDO_LOGO: [u8; 156] = [
+  0x24, 0xFF, 0xAE, 0x51, 0x69, 0x9A, 0xA2, 0x21, 0x3D, 0x84, 0x82, 0x0A, 0x84, 0xE4, 0x09, 0xAD,
+  0x11, 0x24, 0x8B, 0x98, 0xC0, 0x81, 0x7F, 0x21, 0xA3, 0x52, 0xBE, 0x19, 0x93, 0x09, 0xCE, 0x20,
+  0x10, 0x46, 0x4A, 0x4A, 0xF8, 0x27, 0x31, 0xEC, 0x58, 0xC7, 0xE8, 0x33, 0x82, 0xE3, 0xCE, 0xBF,
+  0x85, 0xF4, 0xDF, 0x94, 0xCE, 0x4B, 0x09, 0xC1, 0x94, 0x56, 0x8A, 0xC0, 0x13, 0x72, 0xA7, 0xFC,
+  0x9F, 0x84, 0x4D, 0x73, 0xA3, 0xCA, 0x9A, 0x61, 0x58, 0x97, 0xA3, 0x27, 0xFC, 0x03, 0x98, 0x76,
+  0x23, 0x1D, 0xC7, 0x61, 0x03, 0x04, 0xAE, 0x56, 0xBF, 0x38, 0x84, 0x00, 0x40, 0xA7, 0x0E, 0xFD,
+  0xFF, 0x52, 0xFE, 0x03, 0x6F, 0x95, 0x30, 0xF1, 0x97, 0xFB, 0xC0, 0x85, 0x60, 0xD6, 0x80, 0x25,
+  0xA9, 0x63, 0xBE, 0x03, 0x01, 0x4E, 0x38, 0xE2, 0xF9, 0xA2, 0x34, 0xFF, 0xBB, 0x3E, 0x03, 0x44,
+  0x78, 0x00, 0x90, 0xCB, 0x88, 0x11, 0x3A, 0x94, 0x65, 0xC0, 0x7C, 0x63, 0x87, 0xF0, 0x3C, 0xAF,
+  0xD6, 0x25, 0xE4, 0x8B, 0x38, 0x0A, 0xAC, 0x72, 0x21, 0xD4, 0xF8, 0x07,
+];
+
+/// The 192 byte header at the start of every GBA ROM.
+///
+/// * `0x00`: ARM branch instruction to the start of the program
+/// * `0x04`: The Nintendo logo (see `NINTENDO_LOGO`)
+/// * `0xA0`: Game title, uppercase ASCII padded with 0
+/// * `0xAC`: Game code, uppercase ASCII
+/// * `0xB0`: Maker code, uppercase ASCII
+/// * `0xB2`: Fixed value, must be `0x96`
+/// * `0xB3`: Main unit code, `0x00` for the GBA
+/// * `0xB4`: Device type, normally `0x00`
+/// * `0xB5`: Reserved, should be zero
+/// * `0xBC`: Software version
+/// * `0xBD`: Complement check of bytes `0xA0` through `0xBC`
+/// * `0xBE`: Reserved, should be zero
+///
+/// The BIOS checks the logo and the complement check during boot. The other
+/// fields are informational.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct CartridgeHeader {
+  /// ARM branch instruction to the start of the program.
+  pub entry_point: u32,
+  /// The Nintendo logo, must match `NINTENDO_LOGO`.
+  pub logo: [u8; 156],
+  /// Game title.
+  pub title: [u8; 12],
+  /// Game code.
+  pub game_code: [u8; 4],
+  /// Maker code.
+  pub maker_code: [u8; 2],
+  /// Must be `0x96`.
+  pub fixed_value: u8,
+  /// Main unit code.
+  pub unit_code: u8,
+  /// Device type.
+  pub device_type: u8,
+  /// Reserved.
+  pub reserved1: [u8; 7],
+  /// Software version.
+  pub software_version: u8,
+  /// Complement check, see `compute_complement_check`.
+  pub complement_check: u8,
+  /// Reserved.
+  pub reserved2: [u8; 2],
+}
+impl CartridgeHeader {
+  /// The size of the header in bytes.
+  pub const SIZE: usize = 192;
+
+  /// The required value of the `fixed_value` field.
+  pub const FIXED_VALUE: u8 = 0x96;
+
+  /// Reads a header out of the first 192 bytes of a ROM.
+  pub fn from_bytes(bytes: &[u8; 192]) -> Self {
+    let mut header = CartridgeHeader {
+      entry_point: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+      logo: [0; 156],
+      title: [0; 12],
+      game_code: [0; 4],
+      maker_code: [0; 2],
+      fixed_value: bytes[0xB2],
+      unit_code: bytes[0xB3],
+      device_type: bytes[0xB4],
+      reserved1: [0; 7],
+      software_version: bytes[0xBC],
+      complement_check: bytes[0xBD],
+      reserved2: [bytes[0xBE], bytes[0xBF]],
+    };
+    header.logo.copy_from_slice(&bytes[0x04..0xA0]);
+    header.title.copy_from_slice(&bytes[0xA0..0xAC]);
+    header.game_code.copy_from_slice(&bytes[0xAC..0xB0]);
+    header.maker_code.copy_from_slice(&bytes[0xB0..0xB2]);
+    header.reserved1.copy_from_slice(&bytes[0xB5..0xBC]);
+    header
+  }
+
+  /// Writes the header out as the bytes it would be in a ROM.
+  pub fn to_bytes(&self) -> [u8; 192] {
+    let mut bytes = [0; 192];
+    bytes[0x00..0x04].copy_from_slice(&self.entry_point.to_le_bytes());
+    bytes[0x04..0xA0].copy_from_slice(&self.logo);
+    bytes[0xA0..0xAC].copy_from_slice(&self.title);
+    bytes[0xAC..0xB0].copy_from_slice(&self.game_code);
+    bytes[0xB0..0xB2].copy_from_slice(&self.maker_code);
+    bytes[0xB2] = self.fixed_value;
+    bytes[0xB3] = self.unit_code;
+    bytes[0xB4] = self.device_type;
+    bytes[0xB5..0xBC].copy_from_slice(&self.reserved1);
+    bytes[0xBC] = self.software_version;
+    bytes[0xBD] = self.complement_check;
+    bytes[0xBE..0xC0].copy_from_slice(&self.reserved2);
+    bytes
+  }
+
+  /// Computes the complement check that this header should have.
+  pub fn compute_complement_check(&self) -> u8 {
+    complement_check(&self.to_bytes())
+  }
+
+  /// If the `complement_check` field is correct.
+  pub fn complement_check_is_valid(&self) -> bool {
+    self.complement_check == self.compute_complement_check()
+  }
+
+  /// If the `logo` field matches `NINTENDO_LOGO`.
+  pub fn logo_is_valid(&self) -> bool {
+    self.logo[..] == NINTENDO_LOGO[..]
+  }
+
+  /// If the BIOS will accept this header (the logo, fixed value, and
+  /// complement check are all correct).
+  pub fn is_valid(&self) -> bool {
+    self.logo_is_valid()
+      && self.fixed_value == Self::FIXED_VALUE
+      && self.complement_check_is_valid()
+  }
+}
+impl core::fmt::Debug for CartridgeHeader {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("CartridgeHeader")
+      .field("entry_point", &self.entry_point)
+      .field("logo_is_valid", &self.logo_is_valid())
+      .field("title", &self.title)
+      .field("game_code", &self.game_code)
+      .field("maker_code", &self.maker_code)
+      .field("fixed_value", &self.fixed_value)
+      .field("unit_code", &self.unit_code)
+      .field("device_type", &self.device_type)
+      .field("software_version", &self.software_version)
+      .field("complement_check", &self.complement_check)
+      .finish()
+  }
+}
+
+/// Computes the complement check of the header at the start of a ROM.
+///
+/// This is the negated sum of bytes `0xA0` through `0xBC`, minus `0x19`.
+///
+/// # Panics
+///
+/// If `rom` is shorter than `0xBD` bytes.
+pub fn complement_check(rom: &[u8]) -> u8 {
+  rom[0xA0..=0xBC].iter().fold(0_u8, |chk, &b| chk.wrapping_sub(b)).wrapping_sub(0x19)
+}
+
+/// Fixes up the header at the start of a ROM, in the same way as `gbafix`.
+///
+/// This writes the Nintendo logo and the fixed value, then recomputes the
+/// complement check. The rest of the header is left as-is.
+///
+/// # Panics
+///
+/// If `rom` is shorter than a full header.
+#[cfg(feature = "std")]
+pub fn fix_header(rom: &mut [u8]) {
+  assert!(rom.len() >= CartridgeHeader::SIZE, "ROM is too short to have a header");
+  rom[0x04..0xA0].copy_from_slice(&NINTENDO_LOGO);
+  rom[0xB2] = CartridgeHeader::FIXED_VALUE;
+  rom[0xBD] = complement_check(rom);
+}
+
+newtype! {
+  /// Cart GPIO pin values.
+  ///
+  /// * 0-3: The level of each pin (only meaningful for input pins when
+  ///   reading)
+  ///
+  /// What each pin does depends on the cart. For the RTC, pin 0 is the serial
+  /// clock (SCK), pin 1 is the serial data (SIO), and pin 2 is chip select
+  /// (CS).
+  GpioData, u16
+}
+#[allow(missing_docs)]
+impl GpioData {
+  phantom_fields! {
+    self.0: u16,
+    pin0: 0,
+    pin1: 1,
+    pin2: 2,
+    pin3: 3,
+  }
+}
+
+newtype! {
+  /// Cart GPIO pin directions.
+  ///
+  /// * 0-3: If each pin is an output from the GBA (otherwise it's an input)
+  GpioDirection, u16
+}
+#[allow(missing_docs)]
+impl GpioDirection {
+  phantom_fields! {
+    self.0: u16,
+    pin0_output: 0,
+    pin1_output: 1,
+    pin2_output: 2,
+    pin3_output: 3,
+  }
+}
+
+newtype! {
+  /// Cart GPIO control.
+  ///
+  /// * 0: If the GPIO registers can be read back (otherwise they read as part
+  ///   of the ROM)
+  ///
+  /// The GPIO registers overlap the ROM header area, so reading is disabled by
+  /// default.
+  GpioControl, u16
+}
+#[allow(missing_docs)]
+impl GpioControl {
+  phantom_fields! {
+    self.0: u16,
+    readable: 0,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fixed_point_rounding() {
+    let fp = FP_I_7_8;
+    // Products round to the nearest 256th, with halves going up.
+    assert_eq!(fp(-3) * fp(0xC0), fp(-2));
+    assert_eq!(fp(-1) * fp(0x80), fp(0));
+    assert_eq!(fp(-3) * fp(0x80), fp(-1));
+    assert_eq!(fp(3) * fp(0x80), fp(2));
+    // Quotients round halves away from zero.
+    assert_eq!(fp(1) / FP_I_7_8::from_int(2), fp(1));
+    assert_eq!(fp(-1) / FP_I_7_8::from_int(2), fp(-1));
+    assert_eq!(FP_I_7_8::from_int(3) / FP_I_7_8::from_int(2), fp(0x180));
+  }
+
+  #[test]
+  fn fixed_point_saturating() {
+    let (min, max, one) = (FP_I_7_8::MIN, FP_I_7_8::MAX, FP_I_7_8::ONE);
+    assert_eq!(max.saturating_mul(max), max);
+    assert_eq!(min.saturating_mul(max), min);
+    assert_eq!(min.saturating_mul(min), max);
+    assert_eq!(max.saturating_mul(one), max);
+    assert_eq!(max.saturating_div(FP_I_7_8(1)), max);
+    assert_eq!(min.saturating_div(FP_I_7_8(1)), min);
+    assert_eq!(min.saturating_div(-one), max);
+    assert_eq!(min.saturating_neg(), max);
+    assert_eq!(max.saturating_neg(), FP_I_7_8(-i16::max_value()));
+    assert_eq!(FP_I_19_8::MIN.saturating_mul(FP_I_19_8::MIN), FP_I_19_8::MAX);
+  }
+
+  #[test]
+  fn fixed_point_wrapping() {
+    let (min, one) = (FP_I_7_8::MIN, FP_I_7_8::ONE);
+    // 100.0 * 2.0 = 200.0, which wraps to 200.0 - 256.0.
+    let hundred = FP_I_7_8::from_int(100);
+    assert_eq!(hundred.wrapping_mul(FP_I_7_8::from_int(2)), FP_I_7_8::from_int(-56));
+    assert_eq!(min.wrapping_div(-one), min);
+    assert_eq!(hundred.wrapping_div(FP_I_7_8(0x80)), FP_I_7_8::from_int(-56));
+    assert_eq!(min.wrapping_neg(), min);
+    assert_eq!(FP_I_7_8::MAX.wrapping_add(FP_I_7_8(1)), min);
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  #[should_panic]
+  fn fixed_point_neg_min() {
+    let _ = -FP_I_7_8::MIN;
+  }
+
+  #[test]
+  #[cfg(feature = "float")]
+  fn fixed_point_f32() {
+    assert_eq!(FP_I_7_8::from_f32(1.5), FP_I_7_8(0x180));
+    assert_eq!(FP_I_7_8::from_f32(-1.5), FP_I_7_8(-0x180));
+    // Halves round away from zero.
+    assert_eq!(FP_I_7_8::from_f32(0.5 / 256.0), FP_I_7_8(1));
+    assert_eq!(FP_I_7_8::from_f32(-0.5 / 256.0), FP_I_7_8(-1));
+    assert_eq!(FP_I_7_8::from_f32(0.4 / 256.0), FP_I_7_8(0));
+    assert_eq!(FP_I_7_8::from_f32(1000.0), FP_I_7_8::MAX);
+    assert_eq!(FP_I_7_8::from_f32(-1000.0), FP_I_7_8::MIN);
+    assert_eq!(FP_I_7_8(-0x180).to_f32(), -1.5);
+  }
+
+  /// A header with a title and codes, and everything else zero.
+  fn test_header() -> [u8; 192] {
+    let mut bytes = [0; 192];
+    bytes[0xA0..0xAA].copy_from_slice(b"GBAHALTEST");
+    bytes[0xAC..0xB0].copy_from_slice(b"AGHE");
+    bytes[0xB0..0xB2].copy_from_slice(b"01");
+    bytes[0xB2] = CartridgeHeader::FIXED_VALUE;
+    bytes[0xBC] = 1;
+    bytes
+  }
+
+  #[test]
+  fn header_complement_check() {
+    assert_eq!(complement_check(&test_header()), 0xFB);
+    let mut blank = [0; 192];
+    blank[0xB2] = CartridgeHeader::FIXED_VALUE;
+    assert_eq!(complement_check(&blank), 0x51);
+    // Only 0xA0 through 0xBC count.
+    blank[0x9F] = 1;
+    blank[0xBD] = 1;
+    assert_eq!(complement_check(&blank[..0xBD]), 0x51);
+  }
+
+  #[test]
+  #[should_panic]
+  fn header_complement_check_short() {
+    complement_check(&[0; 0xBC]);
+  }
+
+  #[test]
+  fn header_round_trip() {
+    let mut bytes = test_header();
+    bytes[0..4].copy_from_slice(&0xEA00_002E_u32.to_le_bytes());
+    bytes[0x04..0xA0].copy_from_slice(&NINTENDO_LOGO);
+    bytes[0xB5] = 7;
+    bytes[0xBF] = 9;
+    bytes[0xBD] = complement_check(&bytes);
+    let header = CartridgeHeader::from_bytes(&bytes);
+    assert_eq!(header.entry_point, 0xEA00_002E);
+    assert_eq!(&header.title, b"GBAHALTEST\0\0");
+    assert_eq!(&header.game_code, b"AGHE");
+    assert_eq!(header.reserved1[0], 7);
+    assert_eq!(header.reserved2, [0, 9]);
+    assert!(header.is_valid());
+    assert_eq!(&header.to_bytes()[..], &bytes[..]);
+    let mut bad = header;
+    bad.logo[0] ^= 1;
+    assert!(!bad.logo_is_valid());
+    assert!(!bad.is_valid());
+    bad = header;
+    bad.complement_check = 0;
+    assert!(!bad.complement_check_is_valid());
+    assert!(!bad.is_valid());
+  }
+
+  #[test]
+  #[cfg(feature = "std")]
+  fn header_fix() {
+    let mut rom = std::vec![0xFF; 0x200];
+    rom[..192].copy_from_slice(&test_header());
+    rom[0xB2] = 0;
+    fix_header(&mut rom);
+    let header = CartridgeHeader::from_bytes(&{
+      let mut bytes = [0; 192];
+      bytes.copy_from_slice(&rom[..192]);
+      bytes
+    });
+    assert!(header.logo_is_valid());
+    assert!(header.complement_check_is_valid());
+    assert!(header.is_valid());
+    assert_eq!(header.complement_check, 0xFB);
+    // The rest is left alone.
+    assert_eq!(&rom[0xA0..0xAA], b"GBAHALTEST");
+    assert_eq!(rom[192], 0xFF);
+  }
+
+  #[test]
+  #[cfg(feature = "std")]
+  #[should_panic]
+  fn header_fix_short() {
+    fix_header(&mut [0; 191]);
+  }
+
+  #[test]
+  fn obj_sizes() {
+    use ObjectShape::*;
+    use ObjectSize::*;
+    let table = [
+      (Square, Zero, (1, 1)),
+      (Square, One, (2, 2)),
+      (Square, Two, (4, 4)),
+      (Square, Three, (8, 8)),
+      (Horizontal, Zero, (2, 1)),
+      (Horizontal, One, (4, 1)),
+      (Horizontal, Two, (4, 2)),
+      (Horizontal, Three, (8, 4)),
+      (Vertical, Zero, (1, 2)),
+      (Vertical, One, (1, 4)),
+      (Vertical, Two, (2, 4)),
+      (Vertical, Three, (4, 8)),
+    ];
+    for &(shape, size, (width, height)) in &table {
+      assert_eq!(obj_size_tiles(shape, size), (width, height));
+      assert_eq!(obj_size_pixels(shape, size), (width * 8, height * 8));
+      assert_eq!(obj_tile_count(shape, size, false), width * height);
+      assert_eq!(obj_tile_count(shape, size, true), width * height * 2);
+    }
+  }
+
+  #[test]
+  fn obj_tile_ids() {
+    let one_d = DisplayControl::new().with_oam_memory_1d(true);
+    let two_d = DisplayControl::new();
+    // A 4x2 tile object at tile 100, looking at the tile at (1, 1).
+    assert_eq!(obj_tile_id(one_d, 100, 4, 1, 1, false), 105);
+    assert_eq!(obj_tile_id(one_d, 100, 4, 1, 1, true), 110);
+    assert_eq!(obj_tile_id(two_d, 100, 4, 1, 1, false), 133);
+    assert_eq!(obj_tile_id(two_d, 100, 4, 1, 1, true), 134);
+    // Indexes wrap around the 1024 tiles.
+    assert_eq!(obj_tile_id(one_d, 1023, 4, 1, 0, false), 0);
+    assert_eq!(obj_tile_id(two_d, 1000, 4, 0, 1, false), 8);
+  }
+
+  #[test]
+  fn obj_tile_ranges() {
+    assert_eq!(obj_tile_range(DisplayMode::Mode0), 0..1024);
+    assert_eq!(obj_tile_range(DisplayMode::Mode2), 0..1024);
+    assert_eq!(obj_tile_range(DisplayMode::Mode3), 512..1024);
+    assert_eq!(obj_tile_range(DisplayMode::Mode5), 512..1024);
+  }
+
+  #[test]
+  fn color_rgb888() {
+    for c in 0..32 {
+      let color = Color::rgb(c, 31 - c, c / 2);
+      let (r, g, b) = color.to_rgb888();
+      assert_eq!(Color::from_rgb888(r, g, b), color);
+    }
+    // The LCD curve is flat between 0 and 1, so 1 comes back as 0.
+    for c in 0..32 {
+      let (r, g, b) = Color::rgb(c, c, c).to_rgb888_lcd();
+      let expected = if c == 1 { 0 } else { c };
+      assert_eq!(Color::from_rgb888_lcd(r, g, b), Color::rgb(expected, expected, expected));
+    }
+    assert_eq!(Color::WHITE.to_rgb888(), (255, 255, 255));
+    assert_eq!(Color::WHITE.to_rgb888_lcd(), (255, 255, 255));
+    assert_eq!(Color::from_rgb888(255, 128, 4), Color::rgb(31, 16, 0));
+  }
+
+  #[test]
+  fn color_lerp() {
+    let (black, white) = (Color::BLACK, Color::WHITE);
+    assert_eq!(black.lerp(white, 0), black);
+    assert_eq!(black.lerp(white, 256), white);
+    assert_eq!(black.lerp(white, 1000), white);
+    assert_eq!(black.lerp(white, 128), Color::rgb(16, 16, 16));
+    assert_eq!(white.lerp(black, 128), Color::rgb(16, 16, 16));
+    assert_eq!(Color::RED.lerp(Color::BLUE, 64), Color::rgb(23, 0, 8));
+  }
+
+  #[test]
+  fn color_blend_alpha() {
+    let blend = |eva, evb| BlendAlpha::new().with_eva_coefficient(eva).with_evb_coefficient(evb);
+    let color = Color::rgb(10, 20, 30);
+    assert_eq!(color.blend_alpha(Color::WHITE, blend(16, 0)), color);
+    assert_eq!(color.blend_alpha(Color::WHITE, blend(0, 16)), Color::WHITE);
+    // Coefficients above 16 act as 16.
+    assert_eq!(color.blend_alpha(Color::WHITE, blend(31, 0)), color);
+    assert_eq!(Color::RED.blend_alpha(Color::BLUE, blend(8, 8)), Color::rgb(15, 0, 15));
+    // Channels saturate at 31.
+    assert_eq!(color.blend_alpha(color, blend(16, 16)), Color::rgb(20, 31, 31));
+  }
+
+  #[test]
+  fn color_blend_brightness() {
+    let blend = |evy| BlendBrightness::new().with_evy_coefficient(evy);
+    let color = Color::rgb(0, 10, 31);
+    assert_eq!(color.blend_brightness_increase(blend(0)), color);
+    assert_eq!(color.blend_brightness_decrease(blend(0)), color);
+    assert_eq!(color.blend_brightness_increase(blend(16)), Color::WHITE);
+    assert_eq!(color.blend_brightness_decrease(blend(20)), Color::BLACK);
+    // I + (31 - I) * EVY / 16, and I - I * EVY / 16, rounded down.
+    assert_eq!(color.blend_brightness_increase(blend(8)), Color::rgb(15, 20, 31));
+    assert_eq!(color.blend_brightness_decrease(blend(8)), Color::rgb(0, 5, 16));
+  }
+}
@@ -0,0 +1,524 @@
+//! Decompression of the data formats that the GBA BIOS understands.
+//!
+//! These are plain Rust versions of the BIOS decompression functions. They
+//! work on normal slices, so they can be used on any machine (eg: to check
+//! your assets during a build), and they're also an alternative to the BIOS
+//! calls when running on the GBA itself.
+//!
+//! All of the compressed formats start with a 4-byte [CompressionHeader]
+//! (except for `BitUnPack`, which takes a [BitUnPackInfo] instead). Every
+//! function checks the header and the output space before it starts, and then
+//! returns the number of bytes written.
+
+use gba_proc_macro::phantom_fields;
+
+newtype! {
+  /// The 4-byte header at the start of all BIOS compressed data.
+  ///
+  /// * 0-3: Type specific. The bits per Huffman data unit (4 or 8), or the
+  ///   Diff filter unit size (1 or 2 bytes).
+  /// * 4-7: Compression type
+  /// * 8-31: Decompressed size in bytes
+  CompressionHeader, pub u32
+}
+#[allow(missing_docs)]
+impl CompressionHeader {
+  phantom_fields! {
+    self.0: u32,
+    parameter: 0-3,
+    kind: 4-7,
+    decompressed_size: 8-31,
+  }
+}
+impl CompressionHeader {
+  /// Compression type of LZ77 data.
+  pub const LZ77: u32 = 1;
+  /// Compression type of Huffman data.
+  pub const HUFFMAN: u32 = 2;
+  /// Compression type of Run-Length data.
+  pub const RLE: u32 = 3;
+  /// Compression type of Diff filtered data.
+  pub const DIFF: u32 = 8;
+
+  /// Reads the header from the start of some compressed data.
+  pub fn from_slice(src: &[u8]) -> Result<Self, DecompressError> {
+    if src.len() < 4 {
+      Err(DecompressError::InputTruncated)
+    } else {
+      Ok(CompressionHeader(u32::from_le_bytes([src[0], src[1], src[2], src[3]])))
+    }
+  }
+}
+
+/// The ways that decompression can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecompressError {
+  /// The input ended before the output was complete.
+  InputTruncated,
+  /// The header's compression type isn't the type this function handles.
+  WrongKind {
+    /// The type that the function handles.
+    expected: u32,
+    /// The type given in the header.
+    found: u32,
+  },
+  /// The header's type specific parameter isn't valid for this function.
+  BadParameter(u32),
+  /// The output buffer can't hold the decompressed data.
+  OutputTooSmall {
+    /// Output size required, in units of the output slice.
+    needed: usize,
+  },
+  /// An LZ77 back reference points to before the start of the output.
+  BadDisplacement,
+  /// An LZ77 back reference copies the byte directly before the current
+  /// position, which the VRAM safe version can't do (the BIOS would read back
+  /// a byte that it hasn't written yet).
+  VramUnsafeDisplacement,
+  /// A Huffman tree node points outside of the tree table.
+  BadTree,
+  /// The `BitUnPack` unit widths aren't valid.
+  BadUnitWidth,
+}
+
+/// Checks the header kind and output space, giving the decompressed size.
+fn check_header(
+  src: &[u8], expected: u32, dest_len: usize, unit_size: usize,
+) -> Result<(CompressionHeader, usize), DecompressError> {
+  let header = CompressionHeader::from_slice(src)?;
+  if header.kind() != expected {
+    return Err(DecompressError::WrongKind { expected, found: header.kind() });
+  }
+  let size = header.decompressed_size() as usize;
+  let needed = (size + unit_size - 1) / unit_size;
+  if dest_len < needed {
+    return Err(DecompressError::OutputTooSmall { needed });
+  }
+  Ok((header, size))
+}
+
+/// Gets a byte of input, or an error if the input is too short.
+fn next_byte(src: &[u8], pos: &mut usize) -> Result<u8, DecompressError> {
+  let byte = *src.get(*pos).ok_or(DecompressError::InputTruncated)?;
+  *pos += 1;
+  Ok(byte)
+}
+
+/// Where the LZ77 decoder sends output (and reads back from).
+trait Lz77Output {
+  fn read(&self, i: usize) -> u8;
+  fn write(&mut self, i: usize, byte: u8);
+}
+
+/// Writes single bytes.
+struct ByteOutput<'a>(&'a mut [u8]);
+impl Lz77Output for ByteOutput<'_> {
+  fn read(&self, i: usize) -> u8 {
+    self.0[i]
+  }
+  fn write(&mut self, i: usize, byte: u8) {
+    self.0[i] = byte;
+  }
+}
+
+/// Holds even bytes until the odd byte is ready, and then writes both.
+struct HalfwordOutput<'a> {
+  dest: &'a mut [u16],
+  pending: u16,
+  size: usize,
+}
+impl Lz77Output for HalfwordOutput<'_> {
+  fn read(&self, i: usize) -> u8 {
+    (self.dest[i / 2] >> ((i % 2) * 8)) as u8
+  }
+  fn write(&mut self, i: usize, byte: u8) {
+    if i % 2 == 0 {
+      self.pending = u16::from(byte);
+      if i + 1 == self.size {
+        self.dest[i / 2] = self.pending;
+      }
+    } else {
+      self.dest[i / 2] = self.pending | (u16::from(byte) << 8);
+    }
+  }
+}
+
+/// Runs the LZ77 decoding loop.
+fn lz77_decode(
+  src: &[u8], size: usize, min_distance: usize, output: &mut impl Lz77Output,
+) -> Result<usize, DecompressError> {
+  let mut pos = 4;
+  let mut out = 0;
+  while out < size {
+    let flags = next_byte(src, &mut pos)?;
+    for bit in (0..8).rev() {
+      if out >= size {
+        break;
+      }
+      if flags & (1 << bit) == 0 {
+        output.write(out, next_byte(src, &mut pos)?);
+        out += 1;
+      } else {
+        let b0 = next_byte(src, &mut pos)? as usize;
+        let b1 = next_byte(src, &mut pos)? as usize;
+        let count = (b0 >> 4) + 3;
+        let distance = (((b0 & 0xF) << 8) | b1) + 1;
+        if distance > out {
+          return Err(DecompressError::BadDisplacement);
+        }
+        if distance < min_distance {
+          return Err(DecompressError::VramUnsafeDisplacement);
+        }
+        for _ in 0..count {
+          if out >= size {
+            break;
+          }
+          let byte = output.read(out - distance);
+          output.write(out, byte);
+          out += 1;
+        }
+      }
+    }
+  }
+  Ok(size)
+}
+
+/// LZ77 decompression, writing one byte at a time. BIOS `LZ77UnCompWram`.
+///
+/// Because it writes single bytes, this must not be used to write directly
+/// into VRAM (byte writes to VRAM don't do what you'd want).
+pub fn lz77_uncomp(src: &[u8], dest: &mut [u8]) -> Result<usize, DecompressError> {
+  let (_, size) = check_header(src, CompressionHeader::LZ77, dest.len(), 1)?;
+  lz77_decode(src, size, 1, &mut ByteOutput(dest))
+}
+
+/// LZ77 decompression, writing two bytes at a time. BIOS `LZ77UnCompVram`.
+///
+/// The output is written as whole `u16` values, so this is safe to use with
+/// VRAM. If the decompressed size is odd the final high byte is zero.
+///
+/// Just like the BIOS version, back references with a distance of 1 byte
+/// can't be decoded, and give an error.
+pub fn lz77_uncomp_vram(src: &[u8], dest: &mut [u16]) -> Result<usize, DecompressError> {
+  let (_, size) = check_header(src, CompressionHeader::LZ77, dest.len(), 2)?;
+  lz77_decode(src, size, 2, &mut HalfwordOutput { dest, pending: 0, size })
+}
+
+/// Huffman decompression. BIOS `HuffUnComp`.
+///
+/// The header parameter gives the bits per data unit, 4 or 8. After the header
+/// is the tree size byte, the tree table, and then the bitstream as
+/// little-endian `u32` values that are read from the most significant bit
+/// down.
+///
+/// 4-bit data units are packed two per byte, low nibble first.
+pub fn huff_uncomp(src: &[u8], dest: &mut [u8]) -> Result<usize, DecompressError> {
+  let (header, size) = check_header(src, CompressionHeader::HUFFMAN, dest.len(), 1)?;
+  let bits = header.parameter();
+  if bits != 4 && bits != 8 {
+    return Err(DecompressError::BadParameter(bits));
+  }
+  let tree_size = *src.get(4).ok_or(DecompressError::InputTruncated)? as usize;
+  let stream_start = 4 + (tree_size + 1) * 2;
+  if src.len() < stream_start {
+    return Err(DecompressError::InputTruncated);
+  }
+  //
+  let mut pos = stream_start;
+  let mut word = 0_u32;
+  let mut word_bits = 0;
+  let mut node_addr = 5;
+  let mut out = 0;
+  let mut half_byte: Option<u8> = None;
+  while out < size {
+    if word_bits == 0 {
+      let bytes = src.get(pos..pos + 4).ok_or(DecompressError::InputTruncated)?;
+      word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+      word_bits = 32;
+      pos += 4;
+    }
+    let go_right = word & (1 << 31) != 0;
+    word <<= 1;
+    word_bits -= 1;
+    //
+    let node = src[node_addr];
+    let child_addr = (node_addr & !1) + ((node & 0x3F) as usize) * 2 + 2 + go_right as usize;
+    if child_addr >= stream_start {
+      return Err(DecompressError::BadTree);
+    }
+    let child_is_data = if go_right { node & 0x40 != 0 } else { node & 0x80 != 0 };
+    if child_is_data {
+      let unit = src[child_addr];
+      if bits == 8 {
+        dest[out] = unit;
+        out += 1;
+      } else if let Some(low) = half_byte.take() {
+        dest[out] = low | ((unit & 0xF) << 4);
+        out += 1;
+      } else {
+        half_byte = Some(unit & 0xF);
+      }
+      node_addr = 5;
+    } else {
+      node_addr = child_addr;
+    }
+  }
+  Ok(size)
+}
+
+/// Run-Length decompression. BIOS `RLUnCompWram`.
+///
+/// Each block starts with a flag byte. If bit 7 is set then the next byte is
+/// repeated `(flag & 0x7F) + 3` times, otherwise the next `(flag & 0x7F) + 1`
+/// bytes are copied as-is.
+pub fn rl_uncomp(src: &[u8], dest: &mut [u8]) -> Result<usize, DecompressError> {
+  let (_, size) = check_header(src, CompressionHeader::RLE, dest.len(), 1)?;
+  let mut pos = 4;
+  let mut out = 0;
+  while out < size {
+    let flag = next_byte(src, &mut pos)?;
+    let len = (flag & 0x7F) as usize;
+    if flag & 0x80 != 0 {
+      let byte = next_byte(src, &mut pos)?;
+      for _ in 0..(len + 3) {
+        if out >= size {
+          break;
+        }
+        dest[out] = byte;
+        out += 1;
+      }
+    } else {
+      for _ in 0..(len + 1) {
+        if out >= size {
+          break;
+        }
+        dest[out] = next_byte(src, &mut pos)?;
+        out += 1;
+      }
+    }
+  }
+  Ok(size)
+}
+
+/// Undoes an 8-bit Diff filter. BIOS `Diff8bitUnFilterWram`.
+///
+/// The header parameter must be 1. The first byte is stored as-is, and each
+/// byte after is the (wrapping) difference from the byte before it.
+pub fn diff8bit_unfilter(src: &[u8], dest: &mut [u8]) -> Result<usize, DecompressError> {
+  let (header, size) = check_header(src, CompressionHeader::DIFF, dest.len(), 1)?;
+  if header.parameter() != 1 {
+    return Err(DecompressError::BadParameter(header.parameter()));
+  }
+  let data = src.get(4..4 + size).ok_or(DecompressError::InputTruncated)?;
+  let mut prev = 0_u8;
+  for (d, s) in dest.iter_mut().zip(data.iter()) {
+    prev = prev.wrapping_add(*s);
+    *d = prev;
+  }
+  Ok(size)
+}
+
+/// Undoes a 16-bit Diff filter. BIOS `Diff16bitUnFilter`.
+///
+/// The header parameter must be 2. The data units are little-endian `u16`
+/// values, and work like with `diff8bit_unfilter`. The output is written as
+/// whole `u16` values, so this is safe to use with VRAM.
+pub fn diff16bit_unfilter(src: &[u8], dest: &mut [u16]) -> Result<usize, DecompressError> {
+  let (header, size) = check_header(src, CompressionHeader::DIFF, dest.len(), 2)?;
+  if header.parameter() != 2 {
+    return Err(DecompressError::BadParameter(header.parameter()));
+  }
+  let data = src.get(4..4 + (size & !1)).ok_or(DecompressError::InputTruncated)?;
+  let mut prev = 0_u16;
+  for (d, s) in dest.iter_mut().zip(data.chunks_exact(2)) {
+    prev = prev.wrapping_add(u16::from_le_bytes([s[0], s[1]]));
+    *d = prev;
+  }
+  Ok(size & !1)
+}
+
+/// The settings for `bit_unpack`. This has the same layout as the struct that
+/// the BIOS `BitUnPack` function uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct BitUnPackInfo {
+  /// Length of the source data in bytes.
+  pub src_len: u16,
+  /// Bits per source unit: 1, 2, 4, or 8.
+  pub src_width: u8,
+  /// Bits per destination unit: 1, 2, 4, 8, 16, or 32.
+  pub dest_width: u8,
+  /// * 0-30: Offset added to each unit.
+  /// * 31: Also add the offset to zero units (otherwise zeroes stay zero).
+  pub data_offset: u32,
+}
+
+/// Expands each unit of the source into a wider unit. BIOS `BitUnPack`.
+///
+/// Units are packed from the low bits up in both source and destination. The
+/// output is produced in whole `u32` values (written little-endian), the same
+/// as the BIOS, so any final partial `u32` isn't written.
+pub fn bit_unpack(
+  src: &[u8], dest: &mut [u8], info: &BitUnPackInfo,
+) -> Result<usize, DecompressError> {
+  let src_width = u32::from(info.src_width);
+  let dest_width = u32::from(info.dest_width);
+  match (src_width, dest_width) {
+    (1, 1) | (1, 2) | (1, 4) | (1, 8) | (1, 16) | (1, 32) => (),
+    (2, 2) | (2, 4) | (2, 8) | (2, 16) | (2, 32) => (),
+    (4, 4) | (4, 8) | (4, 16) | (4, 32) => (),
+    (8, 8) | (8, 16) | (8, 32) => (),
+    _ => return Err(DecompressError::BadUnitWidth),
+  }
+  let src_len = info.src_len as usize;
+  let data = src.get(..src_len).ok_or(DecompressError::InputTruncated)?;
+  let needed = (src_len * 8 / src_width as usize) * dest_width as usize / 32 * 4;
+  if dest.len() < needed {
+    return Err(DecompressError::OutputTooSmall { needed });
+  }
+  let offset = info.data_offset & 0x7FFF_FFFF;
+  let offset_zero = info.data_offset & (1 << 31) != 0;
+  let src_mask = (1_u32 << src_width) - 1;
+  //
+  let mut out = 0;
+  let mut word = 0_u32;
+  let mut word_bits = 0;
+  for byte in data.iter() {
+    for shift in (0..8).step_by(src_width as usize) {
+      let mut unit = (u32::from(*byte) >> shift) & src_mask;
+      if unit != 0 || offset_zero {
+        unit = unit.wrapping_add(offset);
+      }
+      word |= unit << word_bits;
+      word_bits += dest_width;
+      if word_bits == 32 {
+        dest[out..out + 4].copy_from_slice(&word.to_le_bytes());
+        out += 4;
+        word = 0;
+        word_bits = 0;
+      }
+    }
+  }
+  Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// `ABCABCABCA`: three literals, then 7 bytes copied from 3 back.
+  const LZ77_ABC: [u8; 10] = [0x10, 0x0A, 0x00, 0x00, 0x10, b'A', b'B', b'C', 0x40, 0x02];
+
+  /// `AAAAA`: one literal, then 4 bytes copied from 1 back.
+  const LZ77_AAA: [u8; 8] = [0x10, 0x05, 0x00, 0x00, 0x40, b'A', 0x10, 0x00];
+
+  #[test]
+  fn header() {
+    assert_eq!(CompressionHeader::from_slice(&[0x10, 0, 0]), Err(DecompressError::InputTruncated));
+    let header = CompressionHeader::from_slice(&LZ77_ABC).unwrap();
+    assert_eq!(header.kind(), CompressionHeader::LZ77);
+    assert_eq!(header.decompressed_size(), 10);
+    assert_eq!(
+      rl_uncomp(&LZ77_ABC, &mut [0; 10]),
+      Err(DecompressError::WrongKind { expected: CompressionHeader::RLE, found: 1 })
+    );
+    assert_eq!(
+      lz77_uncomp(&LZ77_ABC, &mut [0; 9]),
+      Err(DecompressError::OutputTooSmall { needed: 10 })
+    );
+    assert_eq!(
+      lz77_uncomp_vram(&LZ77_ABC, &mut [0; 4]),
+      Err(DecompressError::OutputTooSmall { needed: 5 })
+    );
+  }
+
+  #[test]
+  fn lz77() {
+    let mut out = [0; 10];
+    assert_eq!(lz77_uncomp(&LZ77_ABC, &mut out), Ok(10));
+    assert_eq!(&out, b"ABCABCABCA");
+    let mut out = [0; 5];
+    assert_eq!(lz77_uncomp_vram(&LZ77_ABC, &mut out), Ok(10));
+    assert_eq!(out, [0x4241, 0x4143, 0x4342, 0x4241, 0x4143]);
+    let mut out = [0; 5];
+    assert_eq!(lz77_uncomp(&LZ77_AAA, &mut out), Ok(5));
+    assert_eq!(&out, b"AAAAA");
+  }
+
+  #[test]
+  fn lz77_errors() {
+    assert_eq!(
+      lz77_uncomp_vram(&LZ77_AAA, &mut [0; 3]),
+      Err(DecompressError::VramUnsafeDisplacement)
+    );
+    assert_eq!(
+      lz77_uncomp(&[0x10, 0x03, 0x00, 0x00, 0x80, 0x00, 0x00], &mut [0; 3]),
+      Err(DecompressError::BadDisplacement)
+    );
+    for len in 4..LZ77_ABC.len() {
+      assert_eq!(lz77_uncomp(&LZ77_ABC[..len], &mut [0; 10]), Err(DecompressError::InputTruncated));
+    }
+  }
+
+  #[test]
+  fn huffman() {
+    // a tree with a root and two leaves, 'A' on the left and 'B' on the right
+    let src = [0x28, 3, 0, 0, 1, 0xC0, b'A', b'B', 0x00, 0x00, 0x00, 0x20];
+    let mut out = [0; 3];
+    assert_eq!(huff_uncomp(&src, &mut out), Ok(3));
+    assert_eq!(&out, b"AAB");
+    // 4-bit units 1 and 2, giving a low nibble of 1 and a high nibble of 2
+    let src = [0x24, 1, 0, 0, 1, 0xC0, 1, 2, 0x00, 0x00, 0x00, 0x40];
+    let mut out = [0; 1];
+    assert_eq!(huff_uncomp(&src, &mut out), Ok(1));
+    assert_eq!(out, [0x21]);
+    assert_eq!(huff_uncomp(&src[..10], &mut out), Err(DecompressError::InputTruncated));
+    let bad_tree = [0x28, 1, 0, 0, 1, 0xFF, b'A', b'B', 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(huff_uncomp(&bad_tree, &mut out), Err(DecompressError::BadTree));
+    let bad_bits = [0x25, 1, 0, 0, 1, 0xC0, b'A', b'B', 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(huff_uncomp(&bad_bits, &mut out), Err(DecompressError::BadParameter(5)));
+  }
+
+  #[test]
+  fn rle() {
+    let src = [0x30, 8, 0, 0, 0x82, b'A', 0x02, b'X', b'Y', b'Z'];
+    let mut out = [0; 8];
+    assert_eq!(rl_uncomp(&src, &mut out), Ok(8));
+    assert_eq!(&out, b"AAAAAXYZ");
+    assert_eq!(rl_uncomp(&src[..9], &mut out), Err(DecompressError::InputTruncated));
+  }
+
+  #[test]
+  fn diff() {
+    let src = [0x81, 3, 0, 0, 10, 2, 0xFF];
+    let mut out = [0; 3];
+    assert_eq!(diff8bit_unfilter(&src, &mut out), Ok(3));
+    assert_eq!(out, [10, 12, 11]);
+    assert_eq!(diff8bit_unfilter(&src[..6], &mut out), Err(DecompressError::InputTruncated));
+    let src = [0x82, 6, 0, 0, 0x00, 0x10, 0x03, 0x00, 0xFC, 0xFF];
+    let mut out = [0; 3];
+    assert_eq!(diff16bit_unfilter(&src, &mut out), Ok(6));
+    assert_eq!(out, [0x1000, 0x1003, 0x0FFF]);
+    assert_eq!(
+      diff16bit_unfilter(&src, &mut [0; 2]),
+      Err(DecompressError::OutputTooSmall { needed: 3 })
+    );
+    assert_eq!(
+      diff8bit_unfilter(&[0x82, 1, 0, 0, 0], &mut [0; 1]),
+      Err(DecompressError::BadParameter(2))
+    );
+  }
+
+  #[test]
+  fn bit_unpacking() {
+    let info = BitUnPackInfo { src_len: 1, src_width: 1, dest_width: 4, data_offset: 0 };
+    let mut out = [0; 4];
+    assert_eq!(bit_unpack(&[0b1010_0101], &mut out, &info), Ok(4));
+    assert_eq!(u32::from_le_bytes(out), 0x1010_0101);
+    let info = BitUnPackInfo { data_offset: 2 | (1 << 31), ..info };
+    assert_eq!(bit_unpack(&[0b1010_0101], &mut out, &info), Ok(4));
+    assert_eq!(u32::from_le_bytes(out), 0x3232_2323);
+    let info = BitUnPackInfo { src_width: 3, ..info };
+    assert_eq!(bit_unpack(&[0], &mut out, &info), Err(DecompressError::BadUnitWidth));
+  }
+}
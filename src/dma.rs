@@ -0,0 +1,262 @@
+//! Checked DMA transfers.
+//!
+//! A [DmaTransfer] describes one transfer (addresses, count, and control
+//! bits), and [DmaTransfer::validate] checks it against the limits of a
+//! particular DMA channel. The channels themselves are in the `mmio` module,
+//! and always validate a transfer before starting it.
+//!
+//! The channel limits are:
+//!
+//! * DMA0 can only use internal memory (27-bit addresses), so it can't read
+//!   the game pak, and can move at most 0x4000 units.
+//! * DMA1 and DMA2 can read from anywhere (28-bit addresses) but only write to
+//!   internal memory. They're normally used for the sound FIFOs.
+//! * DMA3 can read and write anywhere.
+//! * DMA1, DMA2, and DMA3 can move at most 0x1_0000 units.
+//! * Only DMA1 and DMA2 can use the `Special` start timing, to feed the sound
+//!   FIFOs. DMA3's video capture mode isn't supported.
+
+use crate::data::{DMAControl, DestAddressControl, SourceAddressControl, StartTiming};
+
+/// The ways that a DMA transfer can be invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmaError {
+  /// The channel isn't 0 through 3.
+  BadChannel,
+  /// The transfer count is 0.
+  ZeroCount,
+  /// The transfer count is more than the channel allows.
+  CountTooLarge {
+    /// The largest count the channel allows.
+    max: u32,
+  },
+  /// An address isn't aligned to the unit size (2 or 4 bytes).
+  Misaligned,
+  /// DMA0 can't read from the game pak.
+  GamePakSource,
+  /// The source address is out of range for this channel.
+  SourceOutOfRange,
+  /// The destination address is out of range for this channel.
+  DestOutOfRange,
+  /// The source and destination slices aren't the same length.
+  LengthMismatch,
+  /// The `Special` start timing isn't supported on this channel.
+  SpecialTimingNotSupported,
+}
+
+/// The start of the game pak region.
+const GAME_PAK_START: usize = 0x800_0000;
+
+/// The end of the 27-bit address range (internal memory only).
+const END_27_BIT: usize = 0x800_0000;
+
+/// The end of the 28-bit address range.
+const END_28_BIT: usize = 0x1000_0000;
+
+/// The largest count a channel allows, or 0 if it's not a channel.
+pub const fn max_count(channel: usize) -> u32 {
+  match channel {
+    0 => 0x4000,
+    1 | 2 | 3 => 0x1_0000,
+    _ => 0,
+  }
+}
+
+/// A description of a DMA transfer.
+///
+/// This starts out as an immediate 16-bit transfer with both addresses
+/// incrementing, and the `with_` methods adjust it from there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmaTransfer {
+  /// The address to read from.
+  pub source: usize,
+  /// The address to write to.
+  pub dest: usize,
+  /// How many units (16-bit or 32-bit) to move.
+  pub count: u32,
+  /// The control bits, which always have `dma_enable` set.
+  pub control: DMAControl,
+}
+impl DmaTransfer {
+  /// Makes an immediate 16-bit transfer with both addresses incrementing.
+  pub const fn new(source: usize, dest: usize, count: u32) -> Self {
+    Self { source, dest, count, control: DMAControl::new().with_dma_enable(true) }
+  }
+
+  /// Sets if the units are 32-bit (otherwise 16-bit).
+  pub const fn with_32bit(self, is_32bit: bool) -> Self {
+    Self { control: self.control.with_dma_is_32bit(is_32bit), ..self }
+  }
+
+  /// Sets how the source address changes after each unit.
+  pub const fn with_source_control(self, ctrl: SourceAddressControl) -> Self {
+    Self { control: self.control.with_src_address_ctrl(ctrl), ..self }
+  }
+
+  /// Sets how the destination address changes after each unit.
+  pub const fn with_dest_control(self, ctrl: DestAddressControl) -> Self {
+    Self { control: self.control.with_dest_address_ctrl(ctrl), ..self }
+  }
+
+  /// Sets when the transfer starts.
+  pub const fn with_timing(self, timing: StartTiming) -> Self {
+    Self { control: self.control.with_dma_start_time(timing), ..self }
+  }
+
+  /// Sets if the transfer repeats at each start time (otherwise it happens
+  /// only once).
+  pub const fn with_repeat(self, repeat: bool) -> Self {
+    Self { control: self.control.with_dma_repeats(repeat), ..self }
+  }
+
+  /// Sets if an interrupt is requested when the transfer ends.
+  pub const fn with_irq(self, irq: bool) -> Self {
+    Self { control: self.control.with_irq_at_end(irq), ..self }
+  }
+
+  /// The unit size in bytes.
+  pub const fn unit_size(&self) -> usize {
+    if self.control.dma_is_32bit() {
+      4
+    } else {
+      2
+    }
+  }
+
+  /// If this is a sound FIFO transfer, where the count is ignored.
+  fn is_sound_fifo(&self, channel: usize) -> bool {
+    (channel == 1 || channel == 2) && self.control.dma_start_time() == StartTiming::Special
+  }
+
+  /// Checks the transfer against the limits of a channel.
+  pub fn validate(&self, channel: usize) -> Result<(), DmaError> {
+    if channel > 3 {
+      return Err(DmaError::BadChannel);
+    }
+    if (channel == 0 || channel == 3) && self.control.dma_start_time() == StartTiming::Special {
+      return Err(DmaError::SpecialTimingNotSupported);
+    }
+    if !self.is_sound_fifo(channel) {
+      if self.count == 0 {
+        return Err(DmaError::ZeroCount);
+      }
+      if self.count > max_count(channel) {
+        return Err(DmaError::CountTooLarge { max: max_count(channel) });
+      }
+    }
+    let unit = self.unit_size();
+    if self.source % unit != 0 || self.dest % unit != 0 {
+      return Err(DmaError::Misaligned);
+    }
+    let source_end = if channel == 0 { END_27_BIT } else { END_28_BIT };
+    if self.source >= source_end {
+      return Err(if channel == 0 && self.source < END_28_BIT && self.source >= GAME_PAK_START {
+        DmaError::GamePakSource
+      } else {
+        DmaError::SourceOutOfRange
+      });
+    }
+    let dest_end = if channel == 3 { END_28_BIT } else { END_27_BIT };
+    if self.dest >= dest_end {
+      return Err(DmaError::DestOutOfRange);
+    }
+    Ok(())
+  }
+
+  /// The value for the count register.
+  ///
+  /// The register is 14 or 16 bits and uses 0 for the largest count, so this
+  /// is just the low 16 bits of the count.
+  pub const fn count_register(&self) -> u16 {
+    self.count as u16
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn address_limits() {
+    let ewram = 0x200_0000;
+    let sram = 0xE00_0000;
+    // DMA0 is 27-bit both ways.
+    assert_eq!(DmaTransfer::new(0x7FF_FFFE, ewram, 1).validate(0), Ok(()));
+    assert_eq!(
+      DmaTransfer::new(0x1000_0000, ewram, 1).validate(0),
+      Err(DmaError::SourceOutOfRange)
+    );
+    assert_eq!(DmaTransfer::new(ewram, 0x7FF_FFFE, 1).validate(0), Ok(()));
+    assert_eq!(DmaTransfer::new(ewram, 0x800_0000, 1).validate(0), Err(DmaError::DestOutOfRange));
+    // DMA1 and DMA2 read 28-bit but write 27-bit.
+    for channel in 1..=2 {
+      assert_eq!(DmaTransfer::new(sram, ewram, 1).validate(channel), Ok(()));
+      assert_eq!(
+        DmaTransfer::new(0x1000_0000, ewram, 1).validate(channel),
+        Err(DmaError::SourceOutOfRange)
+      );
+      assert_eq!(
+        DmaTransfer::new(ewram, 0x800_0000, 1).validate(channel),
+        Err(DmaError::DestOutOfRange)
+      );
+    }
+    // DMA3 is 28-bit both ways.
+    assert_eq!(DmaTransfer::new(sram, 0xFFF_FFFE, 1).validate(3), Ok(()));
+    assert_eq!(
+      DmaTransfer::new(0x1000_0000, ewram, 1).validate(3),
+      Err(DmaError::SourceOutOfRange)
+    );
+    assert_eq!(DmaTransfer::new(ewram, 0x1000_0000, 1).validate(3), Err(DmaError::DestOutOfRange));
+  }
+
+  #[test]
+  fn game_pak_source() {
+    let rom = DmaTransfer::new(0x800_0000, 0x200_0000, 1);
+    assert_eq!(rom.validate(0), Err(DmaError::GamePakSource));
+    let sram = DmaTransfer::new(0xE00_0000, 0x200_0000, 1);
+    assert_eq!(sram.validate(0), Err(DmaError::GamePakSource));
+    assert_eq!(rom.validate(1), Ok(()));
+    assert_eq!(rom.validate(3), Ok(()));
+  }
+
+  #[test]
+  fn count_limits() {
+    let transfer = |count| DmaTransfer::new(0x200_0000, 0x300_0000, count);
+    assert_eq!(transfer(0).validate(3), Err(DmaError::ZeroCount));
+    assert_eq!(transfer(0x4000).validate(0), Ok(()));
+    assert_eq!(transfer(0x4001).validate(0), Err(DmaError::CountTooLarge { max: 0x4000 }));
+    for channel in 1..=3 {
+      assert_eq!(transfer(0x1_0000).validate(channel), Ok(()));
+      assert_eq!(
+        transfer(0x1_0001).validate(channel),
+        Err(DmaError::CountTooLarge { max: 0x1_0000 })
+      );
+    }
+    assert_eq!(transfer(1).validate(4), Err(DmaError::BadChannel));
+    // The largest count is written as 0.
+    assert_eq!(transfer(0x4000).count_register(), 0x4000);
+    assert_eq!(transfer(0x1_0000).count_register(), 0);
+  }
+
+  #[test]
+  fn alignment() {
+    let words = DmaTransfer::new(0x200_0002, 0x300_0000, 1);
+    assert_eq!(words.validate(3), Ok(()));
+    assert_eq!(words.with_32bit(true).validate(3), Err(DmaError::Misaligned));
+    assert_eq!(DmaTransfer::new(0x200_0000, 0x300_0001, 1).validate(3), Err(DmaError::Misaligned));
+  }
+
+  #[test]
+  fn special_timing() {
+    let fifo = DmaTransfer::new(0x200_0000, 0x400_00A0, 4)
+      .with_32bit(true)
+      .with_dest_control(DestAddressControl::Fixed)
+      .with_timing(StartTiming::Special);
+    assert_eq!(fifo.validate(0), Err(DmaError::SpecialTimingNotSupported));
+    assert_eq!(fifo.validate(1), Ok(()));
+    assert_eq!(fifo.validate(2), Ok(()));
+    assert_eq!(fifo.validate(3), Err(DmaError::SpecialTimingNotSupported));
+    // The count is ignored for the sound FIFOs.
+    assert_eq!(DmaTransfer { count: 0, ..fifo }.validate(1), Ok(()));
+  }
+}
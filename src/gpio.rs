@@ -0,0 +1,23 @@
+//! Cart GPIO port access.
+//!
+//! Some carts have a 4 pin GPIO port mapped over part of the ROM header area,
+//! which is used to talk to extra hardware such as a real-time clock, a
+//! rumble motor, or a light sensor.
+
+use crate::data::{GpioControl, GpioData, GpioDirection};
+
+/// Access to the cart GPIO port.
+///
+/// On the GBA you use the implementation in the `mmio` module, and elsewhere
+/// you can use a simulated device for testing, such as the
+/// [SimRtc](crate::rtc::SimRtc) (with the `std` feature).
+pub trait GpioPins {
+  /// Sets the levels of the output pins.
+  fn write_data(&mut self, data: GpioData);
+  /// Reads the levels of all the pins.
+  fn read_data(&mut self) -> GpioData;
+  /// Sets which pins are outputs.
+  fn set_direction(&mut self, dir: GpioDirection);
+  /// Sets the port control.
+  fn set_control(&mut self, ctrl: GpioControl);
+}
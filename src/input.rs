@@ -0,0 +1,373 @@
+//! Frame by frame key handling.
+//!
+//! Read `KEYINPUT` once per frame and pass it to [KeyTracker::update], then
+//! ask the tracker about the buttons. None of this touches the hardware, so
+//! you can drive it with made up `KeyInput` values on any machine.
+//!
+//! Key readings can also be recorded with a [KeyRecorder] and then played back
+//! with a [KeyPlayer], which works as a [KeySource] in place of the hardware.
+//!
+//! ## Recording Format
+//!
+//! A recording is the 4 bytes `KEYS` followed by any number of 4 byte runs.
+//! Each run is a `KeyInput` value (`u16`, little-endian, only the low 10 bits
+//! used) and then the number of frames it lasted (`u16`, little-endian, never
+//! 0). Runs longer than 65535 frames are split up.
+
+use crate::data::{KeyInput, Keys};
+
+/// Tracks the keys across frames, for edge detection and auto-repeat.
+#[derive(Debug, Clone)]
+pub struct KeyTracker {
+  current: Keys,
+  previous: Keys,
+  held_frames: [u16; 10],
+  repeat_delay: u16,
+  repeat_rate: u16,
+}
+impl KeyTracker {
+  /// Makes a tracker with no keys pressed.
+  ///
+  /// Auto-repeat starts after 20 frames and then repeats every 4 frames.
+  pub const fn new() -> Self {
+    Self {
+      current: Keys(0),
+      previous: Keys(0),
+      held_frames: [0; 10],
+      repeat_delay: 20,
+      repeat_rate: 4,
+    }
+  }
+
+  /// Sets the auto-repeat timing, in frames.
+  ///
+  /// A key repeats once it's been held for `delay` frames, and then again
+  /// every `rate` frames after that. A `rate` of 0 turns auto-repeat off.
+  pub fn set_repeat(&mut self, delay: u16, rate: u16) {
+    self.repeat_delay = delay;
+    self.repeat_rate = rate;
+  }
+
+  /// Moves on to the next frame using a new key reading.
+  pub fn update(&mut self, input: KeyInput) {
+    self.previous = self.current;
+    self.current = Keys::from(input);
+    for (bit, frames) in self.held_frames.iter_mut().enumerate() {
+      if self.current.0 & (1 << bit) != 0 {
+        *frames = frames.saturating_add(1);
+      } else {
+        *frames = 0;
+      }
+    }
+  }
+
+  /// Keys that are down this frame.
+  pub fn pressed(&self) -> Keys {
+    self.current
+  }
+
+  /// Keys that are up this frame.
+  pub fn released(&self) -> Keys {
+    !self.current
+  }
+
+  /// Keys that are down this frame and were also down last frame.
+  pub fn held(&self) -> Keys {
+    self.current & self.previous
+  }
+
+  /// Keys that are down this frame but were up last frame.
+  pub fn just_pressed(&self) -> Keys {
+    self.current & !self.previous
+  }
+
+  /// Keys that are up this frame but were down last frame.
+  pub fn just_released(&self) -> Keys {
+    !self.current & self.previous
+  }
+
+  /// Keys that were just pressed, plus any auto-repeats this frame.
+  ///
+  /// This is what you want for moving a menu cursor.
+  pub fn repeated(&self) -> Keys {
+    let mut keys = self.just_pressed();
+    if self.repeat_rate > 0 {
+      for (bit, frames) in self.held_frames.iter().enumerate() {
+        if *frames > self.repeat_delay && (*frames - 1 - self.repeat_delay) % self.repeat_rate == 0
+        {
+          keys.0 |= 1 << bit;
+        }
+      }
+    }
+    keys
+  }
+
+  /// How many frames in a row each key has been down, in the same bit order
+  /// as [Keys] (0 for keys that are up).
+  pub fn held_frames(&self) -> &[u16; 10] {
+    &self.held_frames
+  }
+
+  /// The d-pad left/right value this frame (see `Keys::x_axis`).
+  pub fn x_axis(&self) -> i8 {
+    self.current.x_axis()
+  }
+
+  /// The d-pad up/down value this frame (see `Keys::y_axis`).
+  pub fn y_axis(&self) -> i8 {
+    self.current.y_axis()
+  }
+}
+impl Default for KeyTracker {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Something that gives one key reading per frame.
+///
+/// On the GBA this is normally the `Keypad` handle from the `mmio` module,
+/// but it can also be a [KeyPlayer] replaying a recording.
+pub trait KeySource {
+  /// Gets the key reading for this frame.
+  fn read_keys(&mut self) -> KeyInput;
+}
+
+/// The ways that recording or playback can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingError {
+  /// There's no room left in the buffer.
+  BufferFull,
+  /// The data doesn't start with `KEYS`.
+  BadMagic,
+  /// The data isn't the magic plus a whole number of runs.
+  BadLength,
+  /// The data has a run that lasts 0 frames.
+  EmptyRun,
+}
+
+/// The bytes at the start of every recording.
+pub const RECORDING_MAGIC: [u8; 4] = *b"KEYS";
+
+/// Records key readings into a buffer, using the format in the module docs.
+///
+/// The buffer holds a complete recording after every frame, so you can stop
+/// at any point and just use [as_bytes](KeyRecorder::as_bytes).
+#[derive(Debug)]
+pub struct KeyRecorder<'a> {
+  buf: &'a mut [u8],
+  len: usize,
+}
+impl<'a> KeyRecorder<'a> {
+  /// Starts a recording in the buffer given.
+  pub fn new(buf: &'a mut [u8]) -> Result<Self, RecordingError> {
+    if buf.len() < 4 {
+      return Err(RecordingError::BufferFull);
+    }
+    buf[..4].copy_from_slice(&RECORDING_MAGIC);
+    Ok(Self { buf, len: 4 })
+  }
+
+  /// Records the key reading for one frame.
+  pub fn record(&mut self, input: KeyInput) -> Result<(), RecordingError> {
+    let keys = input.0 & 0b11_1111_1111;
+    if self.len > 4 {
+      let run = &mut self.buf[self.len - 4..self.len];
+      let run_keys = u16::from_le_bytes([run[0], run[1]]);
+      let run_frames = u16::from_le_bytes([run[2], run[3]]);
+      if run_keys == keys && run_frames < u16::max_value() {
+        run[2..4].copy_from_slice(&(run_frames + 1).to_le_bytes());
+        return Ok(());
+      }
+    }
+    if self.buf.len() - self.len < 4 {
+      return Err(RecordingError::BufferFull);
+    }
+    let run = &mut self.buf[self.len..self.len + 4];
+    run[0..2].copy_from_slice(&keys.to_le_bytes());
+    run[2..4].copy_from_slice(&1_u16.to_le_bytes());
+    self.len += 4;
+    Ok(())
+  }
+
+  /// Reads from a source, records the reading, and passes it along.
+  pub fn record_from<S: KeySource>(&mut self, source: &mut S) -> Result<KeyInput, RecordingError> {
+    let input = source.read_keys();
+    self.record(input)?;
+    Ok(input)
+  }
+
+  /// The recording so far.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.buf[..self.len]
+  }
+
+  /// Ends the recording, giving back the part of the buffer that was used.
+  pub fn finish(self) -> &'a [u8] {
+    &self.buf[..self.len]
+  }
+}
+
+/// Plays back a recording made by [KeyRecorder].
+///
+/// After the recording runs out, every frame reads as no keys pressed.
+#[derive(Debug, Clone)]
+pub struct KeyPlayer<'a> {
+  runs: &'a [u8],
+  keys: u16,
+  frames_left: u16,
+}
+impl<'a> KeyPlayer<'a> {
+  /// Checks over a recording and gets ready to play it.
+  pub fn new(data: &'a [u8]) -> Result<Self, RecordingError> {
+    if data.len() < 4 || data[..4] != RECORDING_MAGIC {
+      return Err(RecordingError::BadMagic);
+    }
+    let runs = &data[4..];
+    if runs.len() % 4 != 0 {
+      return Err(RecordingError::BadLength);
+    }
+    if runs.chunks(4).any(|run| run[2] == 0 && run[3] == 0) {
+      return Err(RecordingError::EmptyRun);
+    }
+    Ok(Self { runs, keys: 0b11_1111_1111, frames_left: 0 })
+  }
+
+  /// Gets the next frame's key reading, or `None` if the recording is over.
+  pub fn next_frame(&mut self) -> Option<KeyInput> {
+    if self.frames_left == 0 {
+      if self.runs.is_empty() {
+        return None;
+      }
+      self.keys = u16::from_le_bytes([self.runs[0], self.runs[1]]) & 0b11_1111_1111;
+      self.frames_left = u16::from_le_bytes([self.runs[2], self.runs[3]]);
+      self.runs = &self.runs[4..];
+    }
+    self.frames_left -= 1;
+    Some(KeyInput(self.keys))
+  }
+
+  /// If every frame of the recording has been played.
+  pub fn is_finished(&self) -> bool {
+    self.frames_left == 0 && self.runs.is_empty()
+  }
+}
+impl KeySource for KeyPlayer<'_> {
+  fn read_keys(&mut self) -> KeyInput {
+    self.next_frame().unwrap_or(KeyInput(0b11_1111_1111))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::vec::Vec;
+
+  const A: Keys = Keys(1 << 0);
+  const B: Keys = Keys(1 << 1);
+  const LEFT: Keys = Keys(1 << 5);
+
+  /// Reads from an iterator of frames.
+  struct Source<I>(I);
+  impl<I: Iterator<Item = KeyInput>> KeySource for Source<I> {
+    fn read_keys(&mut self) -> KeyInput {
+      self.0.next().unwrap()
+    }
+  }
+
+  fn frame(tracker: &mut KeyTracker, keys: Keys) {
+    tracker.update(keys.to_key_input());
+  }
+
+  #[test]
+  fn edges() {
+    let mut tracker = KeyTracker::new();
+    assert_eq!(tracker.pressed(), Keys(0));
+    assert_eq!(tracker.released(), Keys::ALL);
+    frame(&mut tracker, A);
+    assert_eq!(tracker.pressed(), A);
+    assert_eq!(tracker.released(), !A);
+    assert_eq!(tracker.just_pressed(), A);
+    assert_eq!(tracker.held(), Keys(0));
+    assert_eq!(tracker.just_released(), Keys(0));
+    frame(&mut tracker, A | B);
+    assert_eq!(tracker.just_pressed(), B);
+    assert_eq!(tracker.held(), A);
+    frame(&mut tracker, B | LEFT);
+    assert_eq!(tracker.just_pressed(), LEFT);
+    assert_eq!(tracker.held(), B);
+    assert_eq!(tracker.just_released(), A);
+    assert_eq!(tracker.x_axis(), -1);
+    assert_eq!(tracker.y_axis(), 0);
+    assert_eq!(tracker.held_frames()[0..2], [0, 2]);
+    frame(&mut tracker, Keys(0));
+    assert_eq!(tracker.just_released(), B | LEFT);
+    assert_eq!(tracker.held_frames(), &[0; 10]);
+  }
+
+  #[test]
+  fn repeat() {
+    let mut tracker = KeyTracker::new();
+    tracker.set_repeat(3, 2);
+    let repeats: Vec<bool> = (0..9)
+      .map(|_| {
+        frame(&mut tracker, A);
+        tracker.repeated() == A
+      })
+      .collect();
+    assert_eq!(repeats, [true, false, false, true, false, true, false, true, false]);
+    tracker.set_repeat(3, 0);
+    frame(&mut tracker, A);
+    frame(&mut tracker, A);
+    assert_eq!(tracker.repeated(), Keys(0));
+  }
+
+  #[test]
+  fn record_and_play() {
+    let frames = [Keys(0), A, A, A | B, Keys(0), Keys(0)];
+    let mut buf = [0; 64];
+    let mut recorder = KeyRecorder::new(&mut buf).unwrap();
+    recorder.record(frames[0].to_key_input()).unwrap();
+    let mut source = Source(frames[1..].iter().map(|keys| keys.to_key_input()));
+    for &keys in frames[1..].iter() {
+      assert_eq!(recorder.record_from(&mut source), Ok(keys.to_key_input()));
+    }
+    let data = recorder.finish();
+    assert_eq!(data, b"KEYS\xFF\x03\x01\x00\xFE\x03\x02\x00\xFC\x03\x01\x00\xFF\x03\x02\x00");
+    let mut player = KeyPlayer::new(data).unwrap();
+    for &keys in frames.iter() {
+      assert!(!player.is_finished());
+      assert_eq!(player.read_keys(), keys.to_key_input());
+    }
+    assert!(player.is_finished());
+    assert_eq!(player.next_frame(), None);
+    assert_eq!(player.read_keys(), Keys(0).to_key_input());
+  }
+
+  #[test]
+  fn long_runs() {
+    let mut buf = [0; 12];
+    let mut recorder = KeyRecorder::new(&mut buf).unwrap();
+    for _ in 0..0x1_0001 {
+      recorder.record(A.to_key_input()).unwrap();
+    }
+    assert_eq!(recorder.record(B.to_key_input()), Err(RecordingError::BufferFull));
+    let data = recorder.finish();
+    assert_eq!(data[4..], [0xFE, 0x03, 0xFF, 0xFF, 0xFE, 0x03, 0x02, 0x00]);
+    let mut player = KeyPlayer::new(data).unwrap();
+    assert_eq!((0..).take_while(|_| player.next_frame().is_some()).count(), 0x1_0001);
+    assert_eq!(KeyRecorder::new(&mut [0; 3]).unwrap_err(), RecordingError::BufferFull);
+  }
+
+  #[test]
+  fn bad_recordings() {
+    assert_eq!(KeyPlayer::new(b"").unwrap_err(), RecordingError::BadMagic);
+    assert_eq!(KeyPlayer::new(b"KEY").unwrap_err(), RecordingError::BadMagic);
+    assert_eq!(KeyPlayer::new(b"KEYZ\xFF\x03\x01\x00").unwrap_err(), RecordingError::BadMagic);
+    assert_eq!(KeyPlayer::new(b"KEYS\xFF\x03\x01").unwrap_err(), RecordingError::BadLength);
+    assert_eq!(KeyPlayer::new(b"KEYS\xFF\x03\x00\x00").unwrap_err(), RecordingError::EmptyRun);
+    let mut player = KeyPlayer::new(b"KEYS").unwrap();
+    assert!(player.is_finished());
+    assert_eq!(player.next_frame(), None);
+  }
+}
@@ -0,0 +1,180 @@
+//! A table of Rust interrupt handlers.
+//!
+//! The [IrqDispatcher] holds one `fn()` per interrupt source, and its
+//! [dispatch](IrqDispatcher::dispatch) calls the handlers for a set of pending
+//! interrupts. With the `irq_runtime` feature, the `mmio` module has a master
+//! interrupt handler that uses a global dispatcher, but the table itself is
+//! ordinary Rust that works anywhere.
+//!
+//! [acknowledge] does the register side of handling an interrupt through a
+//! [RegisterBus], so a whole handler can be tested off the GBA.
+
+use crate::{
+  bus::RegisterBus,
+  data::{Interrupt, InterruptEnable, InterruptRequestFlags},
+};
+
+/// `IE`
+const IE_ADDRESS: usize = 0x400_0200;
+/// `IF`
+const IF_ADDRESS: usize = 0x400_0202;
+/// The BIOS's copy of the interrupt flags, `IntrCheck`.
+const INTR_CHECK_ADDRESS: usize = 0x300_7FF8;
+
+/// Acknowledges the pending interrupts, the same as a master interrupt handler
+/// should before calling any handlers.
+///
+/// The interrupts that are both enabled in `IE` and flagged in `IF` are
+/// cleared from `IF` (by writing them back), and added to the BIOS's copy of
+/// the flags so that `IntrWait` sees them. Gives back those interrupts, ready
+/// to [dispatch](IrqDispatcher::dispatch).
+pub fn acknowledge<B: RegisterBus>(bus: &mut B) -> InterruptRequestFlags {
+  let pending = bus.read_u16(IE_ADDRESS) & bus.read_u16(IF_ADDRESS);
+  bus.write_u16(IF_ADDRESS, pending);
+  bus.modify_u16(INTR_CHECK_ADDRESS, |check| check | pending);
+  InterruptRequestFlags::from_bits(pending)
+}
+
+/// One handler per interrupt source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IrqDispatcher {
+  handlers: [Option<fn()>; 14],
+}
+impl IrqDispatcher {
+  /// Makes a dispatcher with no handlers.
+  pub const fn new() -> Self {
+    Self { handlers: [None; 14] }
+  }
+
+  /// Sets (or clears) the handler for an interrupt, giving back the old one.
+  pub fn set_handler(&mut self, irq: Interrupt, handler: Option<fn()>) -> Option<fn()> {
+    core::mem::replace(&mut self.handlers[irq as usize], handler)
+  }
+
+  /// Gets the handler for an interrupt.
+  pub fn handler(&self, irq: Interrupt) -> Option<fn()> {
+    self.handlers[irq as usize]
+  }
+
+  /// The interrupts that have a handler, ready to write to `IE`.
+  pub fn handled(&self) -> InterruptEnable {
+    let mut bits = 0;
+    for irq in Interrupt::ALL.iter() {
+      if self.handlers[*irq as usize].is_some() {
+        bits |= irq.mask();
+      }
+    }
+    InterruptEnable::from_bits(bits)
+  }
+
+  /// Calls the handler of each pending interrupt, in priority order (lowest
+  /// bit first). Pending interrupts without a handler are skipped.
+  ///
+  /// Gives back how many handlers were called.
+  pub fn dispatch(&self, pending: InterruptRequestFlags) -> usize {
+    let mut called = 0;
+    for irq in Interrupt::ALL.iter() {
+      if pending.bits() & irq.mask() != 0 {
+        if let Some(handler) = self.handlers[*irq as usize] {
+          handler();
+          called += 1;
+        }
+      }
+    }
+    called
+  }
+}
+impl Default for IrqDispatcher {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    bus::{Access, AccessKind, MockBus, Width},
+    sim::SimBus,
+  };
+  use core::sync::atomic::{AtomicUsize, Ordering};
+
+  static VBLANK_CALLS: AtomicUsize = AtomicUsize::new(0);
+  static SERIAL_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+  fn on_vblank() {
+    VBLANK_CALLS.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn on_serial() {
+    // VBlank is the lower bit, so its handler already ran.
+    let serial_calls = SERIAL_CALLS.fetch_add(1, Ordering::SeqCst);
+    assert_eq!(VBLANK_CALLS.load(Ordering::SeqCst), serial_calls + 1);
+  }
+
+  #[test]
+  fn handlers() {
+    let mut dispatcher = IrqDispatcher::new();
+    assert_eq!(dispatcher.handled(), InterruptEnable::from_bits(0));
+    assert!(dispatcher.set_handler(Interrupt::VBlank, Some(on_vblank)).is_none());
+    assert!(dispatcher.set_handler(Interrupt::Serial, Some(on_serial)).is_none());
+    assert!(dispatcher.handler(Interrupt::VBlank).is_some());
+    assert!(dispatcher.handler(Interrupt::Timer0).is_none());
+    assert_eq!(
+      dispatcher.handled(),
+      InterruptEnable::from_bits(Interrupt::VBlank.mask() | Interrupt::Serial.mask())
+    );
+    assert!(dispatcher.set_handler(Interrupt::Serial, None).is_some());
+    assert_eq!(dispatcher.handled(), InterruptEnable::from_bits(Interrupt::VBlank.mask()));
+  }
+
+  #[test]
+  fn dispatch_through_sim() {
+    let mut dispatcher = IrqDispatcher::new();
+    dispatcher.set_handler(Interrupt::VBlank, Some(on_vblank));
+    dispatcher.set_handler(Interrupt::Serial, Some(on_serial));
+    let mut bus = SimBus::new();
+    let enabled = Interrupt::VBlank.mask() | Interrupt::Serial.mask() | Interrupt::Timer0.mask();
+    bus.write_u16(IE_ADDRESS, enabled);
+    bus.write_u16(INTR_CHECK_ADDRESS, Interrupt::Dma3.mask());
+    bus.write_u16(0x400_0208, 1);
+    bus.raise(Interrupt::VBlank);
+    bus.raise(Interrupt::Serial);
+    bus.raise(Interrupt::Timer0);
+    bus.raise(Interrupt::Keypad);
+    assert!(bus.irq_requested());
+    //
+    let pending = acknowledge(&mut bus);
+    assert_eq!(pending.bits(), enabled);
+    // Timer 0 has no handler, so it's acknowledged but skipped.
+    assert_eq!(dispatcher.dispatch(pending), 2);
+    assert_eq!(VBLANK_CALLS.load(Ordering::SeqCst), 1);
+    assert_eq!(SERIAL_CALLS.load(Ordering::SeqCst), 1);
+    // Only the interrupt that isn't enabled is left.
+    assert_eq!(bus.read_u16(IF_ADDRESS), Interrupt::Keypad.mask());
+    assert!(!bus.irq_requested());
+    assert_eq!(bus.read_u16(INTR_CHECK_ADDRESS), enabled | Interrupt::Dma3.mask());
+    // With nothing new pending, acknowledging again does nothing.
+    assert_eq!(acknowledge(&mut bus).bits(), 0);
+  }
+
+  #[test]
+  fn acknowledge_accesses() {
+    let mut bus = MockBus::new();
+    bus.set(IE_ADDRESS, Width::U16, 0b0101);
+    bus.set(IF_ADDRESS, Width::U16, 0b0110);
+    bus.set(INTR_CHECK_ADDRESS, Width::U16, 0b1000);
+    assert_eq!(acknowledge(&mut bus).bits(), 0b0100);
+    let access = |kind, address, value| Access { kind, address, width: Width::U16, value };
+    assert_eq!(
+      bus.accesses(),
+      &[
+        access(AccessKind::Read, IE_ADDRESS, 0b0101),
+        access(AccessKind::Read, IF_ADDRESS, 0b0110),
+        access(AccessKind::Write, IF_ADDRESS, 0b0100),
+        access(AccessKind::Read, INTR_CHECK_ADDRESS, 0b1000),
+        access(AccessKind::Write, INTR_CHECK_ADDRESS, 0b1100),
+      ]
+    );
+  }
+}
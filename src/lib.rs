@@ -1,8 +1,12 @@
 #![no_std]
+#![cfg_attr(all(feature = "irq_runtime", target_arch = "arm"), feature(global_asm))]
 #![warn(missing_docs)]
 
 //! The bare bones hardware abstraction layer for the GBA.
 
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
 /// Assists in defining a newtype wrapper over some base type.
 ///
 /// Note that rustdoc and derives are all the "meta" stuff, so you can write all
@@ -91,6 +95,36 @@ macro_rules! newtype_enum {
 
 pub mod data;
 
+pub mod decompress;
+
+pub mod affine;
+
+pub mod oam;
+
+pub mod palette;
+
+pub mod input;
+
+pub mod dma;
+
+pub mod irq;
+
+pub mod sync;
+
+pub mod bus;
+
+pub mod save;
+
+pub mod gpio;
+
+pub mod rtc;
+
+#[cfg(feature = "std")]
+pub mod compress;
+
+#[cfg(any(test, feature = "std"))]
+pub mod sim;
+
 #[cfg(any(
   all(target_env = "agb", target_vendor = "nintendo"),
   feature = "unsafe_docs_rs_mmio_listing_override"
@@ -27,12 +27,44 @@ pub use dma::*;
 mod timers;
 pub use timers::*;
 
+mod serial;
+pub use serial::*;
+
 mod keypad;
 pub use keypad::*;
 
 mod irq;
 pub use irq::*;
 
+mod system;
+pub use system::*;
+
+mod save;
+pub use save::*;
+
+mod gpio;
+pub use gpio::*;
+
+mod vram;
+pub use vram::*;
+
+mod bitmap;
+pub use bitmap::*;
+
+mod affine;
+
+mod oam;
+pub use oam::*;
+
+mod palette;
+pub use palette::*;
+
+mod peripherals;
+pub use peripherals::*;
+
+mod bus;
+pub use bus::*;
+
 /// Background colors.
 ///
 /// The 0th slot of this is the "backdrop" color, which is displayed in a pixel
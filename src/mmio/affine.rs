@@ -0,0 +1,49 @@
+use super::*;
+use crate::affine::{AffineMatrix, BgAffineParams};
+
+impl AffineMatrix {
+  /// Writes the matrix to `BG2PA` through `BG2PD`.
+  pub fn write_bg2(&self) {
+    BG2PA.write(self.pa);
+    BG2PB.write(self.pb);
+    BG2PC.write(self.pc);
+    BG2PD.write(self.pd);
+  }
+
+  /// Writes the matrix to `BG3PA` through `BG3PD`.
+  pub fn write_bg3(&self) {
+    BG3PA.write(self.pa);
+    BG3PB.write(self.pb);
+    BG3PC.write(self.pc);
+    BG3PD.write(self.pd);
+  }
+
+  /// Writes the matrix to object affine slot `n`, using `AFFINE_PARAMS_A`
+  /// through `AFFINE_PARAMS_D`.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 32 or more.
+  pub fn write_obj(&self, n: usize) {
+    AFFINE_PARAMS_A.index(n).write(self.pa.0);
+    AFFINE_PARAMS_B.index(n).write(self.pb.0);
+    AFFINE_PARAMS_C.index(n).write(self.pc.0);
+    AFFINE_PARAMS_D.index(n).write(self.pd.0);
+  }
+}
+
+impl BgAffineParams {
+  /// Writes the parameters to `BG2PA` through `BG2Y`.
+  pub fn write_bg2(&self) {
+    self.matrix.write_bg2();
+    BG2X.write(self.x);
+    BG2Y.write(self.y);
+  }
+
+  /// Writes the parameters to `BG3PA` through `BG3Y`.
+  pub fn write_bg3(&self) {
+    self.matrix.write_bg3();
+    BG3X.write(self.x);
+    BG3Y.write(self.y);
+  }
+}
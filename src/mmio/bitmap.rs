@@ -0,0 +1,213 @@
+use super::*;
+use typenum::{
+  consts::{U120, U128, U160, U240},
+  Prod,
+};
+
+/// One of the two bitmap pages used by Mode4 and Mode5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+  /// The page at the start of VRAM.
+  Zero,
+  /// The page at `PAGE1_OFFSET`.
+  One,
+}
+impl Page {
+  /// The page that a display control value shows (`frame1`).
+  pub const fn from_display_control(ctrl: DisplayControl) -> Self {
+    if ctrl.frame1() {
+      Page::One
+    } else {
+      Page::Zero
+    }
+  }
+
+  /// Sets a display control value to show this page.
+  pub const fn apply(self, ctrl: DisplayControl) -> DisplayControl {
+    ctrl.with_frame1(match self {
+      Page::Zero => false,
+      Page::One => true,
+    })
+  }
+
+  /// The other page.
+  pub const fn other(self) -> Self {
+    match self {
+      Page::Zero => Page::One,
+      Page::One => Page::Zero,
+    }
+  }
+
+  /// The address that the page starts at.
+  pub const fn base_address(self) -> usize {
+    match self {
+      Page::Zero => VRAM_BASE_USIZE,
+      Page::One => VRAM_BASE_USIZE + PAGE1_OFFSET,
+    }
+  }
+
+  /// The page that `DISPCNT` is currently showing.
+  pub fn displayed() -> Self {
+    Self::from_display_control(DISPCNT.read())
+  }
+
+  /// Sets `DISPCNT` to show this page.
+  pub fn display(self) {
+    DISPCNT.write(self.apply(DISPCNT.read()))
+  }
+}
+
+/// Swaps which page is shown, giving the page that's now hidden.
+///
+/// The page given back is the one that you should draw the next frame on.
+pub fn flip_page() -> Page {
+  let shown = Page::displayed().other();
+  shown.display();
+  shown.other()
+}
+
+/// Mode3 is a single page of full color pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Mode3;
+impl Mode3 {
+  /// Screen width in pixels.
+  pub const WIDTH: usize = 240;
+
+  /// Screen height in pixels.
+  pub const HEIGHT: usize = 160;
+
+  /// The bitmap, in row-major order.
+  pub const VRAM: VolBlock<Color, Prod<U240, U160>> = unsafe { VolBlock::new(VRAM_BASE_USIZE) };
+
+  /// Reads the pixel at `(x, y)`, if it's on the screen.
+  pub fn read(x: usize, y: usize) -> Option<Color> {
+    if x < Self::WIDTH && y < Self::HEIGHT {
+      Some(Self::VRAM.index(y * Self::WIDTH + x).read())
+    } else {
+      None
+    }
+  }
+
+  /// Writes the pixel at `(x, y)`, if it's on the screen.
+  pub fn write(x: usize, y: usize, color: Color) -> Option<()> {
+    if x < Self::WIDTH && y < Self::HEIGHT {
+      Self::VRAM.index(y * Self::WIDTH + x).write(color);
+      Some(())
+    } else {
+      None
+    }
+  }
+
+  /// Fills the whole screen with one color.
+  pub fn clear_to(color: Color) {
+    Self::VRAM.iter().for_each(|addr| addr.write(color));
+  }
+}
+
+/// Mode4 is two pages of 8bpp pixels, which index into `PALRAM_BG`.
+///
+/// VRAM can't be written one byte at a time (a byte write puts the value in
+/// both bytes of the halfword), so the pixels are accessed as `u16` pairs. The
+/// pixel with the even `x` is in the low byte of each pair.
+#[derive(Debug, Clone, Copy)]
+pub struct Mode4;
+impl Mode4 {
+  /// Screen width in pixels.
+  pub const WIDTH: usize = 240;
+
+  /// Screen height in pixels.
+  pub const HEIGHT: usize = 160;
+
+  /// Gets the bitmap of a page, as row-major pixel pairs.
+  pub const fn page(page: Page) -> VolBlock<u16, Prod<U120, U160>> {
+    unsafe { VolBlock::new(page.base_address()) }
+  }
+
+  /// Reads the pixel at `(x, y)` of a page, if it's on the screen.
+  pub fn read(page: Page, x: usize, y: usize) -> Option<u8> {
+    if x < Self::WIDTH && y < Self::HEIGHT {
+      let pair = Self::page(page).index((y * Self::WIDTH + x) / 2).read();
+      Some(if x % 2 == 0 { pair as u8 } else { (pair >> 8) as u8 })
+    } else {
+      None
+    }
+  }
+
+  /// Writes the pixel at `(x, y)` of a page, if it's on the screen.
+  ///
+  /// This has to read the pair first to keep the other pixel, so if you can,
+  /// write whole pairs with [write_pair](Mode4::write_pair) instead.
+  pub fn write(page: Page, x: usize, y: usize, index: u8) -> Option<()> {
+    if x < Self::WIDTH && y < Self::HEIGHT {
+      let addr = Self::page(page).index((y * Self::WIDTH + x) / 2);
+      let pair = addr.read();
+      addr.write(if x % 2 == 0 {
+        (pair & 0xFF00) | u16::from(index)
+      } else {
+        (pair & 0x00FF) | (u16::from(index) << 8)
+      });
+      Some(())
+    } else {
+      None
+    }
+  }
+
+  /// Writes the two pixels at `(x, y)` and `(x+1, y)` of a page, if they're
+  /// on the screen. `x` must be even.
+  pub fn write_pair(page: Page, x: usize, y: usize, left: u8, right: u8) -> Option<()> {
+    if x % 2 == 0 && x < Self::WIDTH && y < Self::HEIGHT {
+      Self::page(page)
+        .index((y * Self::WIDTH + x) / 2)
+        .write(u16::from(left) | (u16::from(right) << 8));
+      Some(())
+    } else {
+      None
+    }
+  }
+
+  /// Fills all of a page with one palette index.
+  pub fn clear_to(page: Page, index: u8) {
+    let pair = u16::from(index) | (u16::from(index) << 8);
+    Self::page(page).iter().for_each(|addr| addr.write(pair));
+  }
+}
+
+/// Mode5 is two pages of full color pixels, but only 160x128.
+#[derive(Debug, Clone, Copy)]
+pub struct Mode5;
+impl Mode5 {
+  /// Bitmap width in pixels.
+  pub const WIDTH: usize = 160;
+
+  /// Bitmap height in pixels.
+  pub const HEIGHT: usize = 128;
+
+  /// Gets the bitmap of a page, in row-major order.
+  pub const fn page(page: Page) -> VolBlock<Color, Prod<U160, U128>> {
+    unsafe { VolBlock::new(page.base_address()) }
+  }
+
+  /// Reads the pixel at `(x, y)` of a page, if it's within the bitmap.
+  pub fn read(page: Page, x: usize, y: usize) -> Option<Color> {
+    if x < Self::WIDTH && y < Self::HEIGHT {
+      Some(Self::page(page).index(y * Self::WIDTH + x).read())
+    } else {
+      None
+    }
+  }
+
+  /// Writes the pixel at `(x, y)` of a page, if it's within the bitmap.
+  pub fn write(page: Page, x: usize, y: usize, color: Color) -> Option<()> {
+    if x < Self::WIDTH && y < Self::HEIGHT {
+      Self::page(page).index(y * Self::WIDTH + x).write(color);
+      Some(())
+    } else {
+      None
+    }
+  }
+
+  /// Fills all of a page with one color.
+  pub fn clear_to(page: Page, color: Color) {
+    Self::page(page).iter().for_each(|addr| addr.write(color));
+  }
+}
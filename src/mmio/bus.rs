@@ -0,0 +1,124 @@
+
+use super::*;
+use crate::bus::{RegisterBus, Width};
+
+/// The address ranges that a [HardwareBus] will access: the IO registers,
+/// palette RAM, VRAM, and OAM.
+const HARDWARE_BUS_WINDOWS: [(usize, usize); 4] = [
+  (0x400_0000, 0x400_0400),
+  (0x500_0000, 0x500_0400),
+  (0x600_0000, 0x601_8000),
+  (0x700_0000, 0x700_0400),
+];
+
+/// The DMA registers, which a [HardwareBus] won't access, since a DMA
+/// transfer can write to any memory.
+const HARDWARE_BUS_DMA: (usize, usize) = (0x400_00B0, 0x400_00E0);
+
+/// If a [HardwareBus] can access an address at a width.
+///
+/// The address has to be aligned to the width and fully inside one of the
+/// IO, palette, VRAM, or OAM ranges, and not in the DMA registers.
+pub fn hardware_bus_allows(address: usize, width: Width) -> bool {
+  let end = match address.checked_add(width.bytes()) {
+    Some(end) => end,
+    None => return false,
+  };
+  let (dma_start, dma_end) = HARDWARE_BUS_DMA;
+  address % width.bytes() == 0
+    && !(address < dma_end && end > dma_start)
+    && HARDWARE_BUS_WINDOWS.iter().any(|&(start, stop)| address >= start && end <= stop)
+}
+
+/// A [RegisterBus] that does real volatile accesses.
+///
+/// Only the addresses allowed by [hardware_bus_allows] are accessed, so that
+/// safe code can't use the bus to touch arbitrary memory.
+///
+/// # Panics
+///
+/// Any access to an address that isn't allowed.
+#[derive(Debug)]
+pub struct HardwareBus {
+  _priv: (),
+}
+impl HardwareBus {
+  /// Makes the bus.
+  ///
+  /// # Safety
+  ///
+  /// Nothing else can be using the registers that the bus is given, since
+  /// the bus accesses them without any of the other handles.
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+
+  fn check(address: usize, width: Width) {
+    assert!(
+      hardware_bus_allows(address, width),
+      "HardwareBus can't access {:#X} as {:?}",
+      address,
+      width
+    );
+  }
+}
+impl RegisterBus for HardwareBus {
+  fn read(&mut self, address: usize, width: Width) -> u32 {
+    Self::check(address, width);
+    unsafe {
+      match width {
+        Width::U8 => u32::from(VolAddress::<u8>::new(address).read()),
+        Width::U16 => u32::from(VolAddress::<u16>::new(address).read()),
+        Width::U32 => VolAddress::<u32>::new(address).read(),
+      }
+    }
+  }
+
+  fn write(&mut self, address: usize, width: Width, value: u32) {
+    Self::check(address, width);
+    unsafe {
+      match width {
+        Width::U8 => VolAddress::<u8>::new(address).write(value as u8),
+        Width::U16 => VolAddress::<u16>::new(address).write(value as u16),
+        Width::U32 => VolAddress::<u32>::new(address).write(value),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn allowed_addresses() {
+    assert!(hardware_bus_allows(0x400_0000, Width::U16));
+    assert!(hardware_bus_allows(0x400_0200, Width::U32));
+    assert!(hardware_bus_allows(0x500_03FF, Width::U8));
+    assert!(hardware_bus_allows(0x601_7FFC, Width::U32));
+    assert!(hardware_bus_allows(0x700_03FE, Width::U16));
+    // Null and misaligned.
+    assert!(!hardware_bus_allows(0, Width::U8));
+    assert!(!hardware_bus_allows(0x400_0001, Width::U16));
+    assert!(!hardware_bus_allows(0x400_0002, Width::U32));
+    // Outside the windows, or running off the end of one.
+    assert!(!hardware_bus_allows(0x200_0000, Width::U32));
+    assert!(!hardware_bus_allows(0x300_7FFC, Width::U32));
+    assert!(!hardware_bus_allows(0x400_0400, Width::U8));
+    assert!(!hardware_bus_allows(0x601_8000, Width::U16));
+    assert!(!hardware_bus_allows(0x800_0000, Width::U16));
+    assert!(!hardware_bus_allows(usize::max_value() - 3, Width::U32));
+    // The DMA registers.
+    assert!(hardware_bus_allows(0x400_00AC, Width::U32));
+    assert!(!hardware_bus_allows(0x400_00B0, Width::U32));
+    assert!(!hardware_bus_allows(0x400_00DE, Width::U16));
+    assert!(hardware_bus_allows(0x400_00E0, Width::U16));
+  }
+
+  #[test]
+  #[should_panic]
+  fn rejects_null() {
+    let mut bus = unsafe { HardwareBus::new() };
+    bus.read_u8(0);
+  }
+}
@@ -1,5 +1,6 @@
 
 use super::*;
+use crate::dma::{DmaError, DmaTransfer};
 
 /// Only uses the least significant 27 bits of the address
 pub const DMA0_SOURCE: WOVolAddress<*mut u32> = unsafe { WOVolAddress::new(0x4_000_0B0) };
@@ -90,3 +91,185 @@ pub const DMA3_CONTROL: VolAddress<DMAControl> = unsafe { VolAddress::new(0x4_00
 pub unsafe fn set_dma3_control(ctrl: DMAControl) {
   VolAddress::new(DMA3_CONTROL.to_usize()).write(ctrl)
 }
+
+/// One of the four DMA channels, with checked transfers.
+///
+/// Every transfer is checked with `DmaTransfer::validate` before any register
+/// is written, so an invalid transfer gives an error instead of doing
+/// something unexpected.
+///
+/// There's only one of each channel, which you get from `Peripherals::take`.
+/// Only channels 0 through 3 exist, so those are the only ones with a `new`:
+///
+/// ```compile_fail
+/// let dma4 = unsafe { gba_hal::mmio::DmaChannel::<4>::new() };
+/// ```
+#[derive(Debug)]
+pub struct DmaChannel<const N: usize> {
+  _priv: (),
+}
+
+macro_rules! impl_dma_new {
+  ($($n:literal),*) => {
+    $(
+      impl DmaChannel<$n> {
+        /// Makes a handle to the channel.
+        ///
+        /// # Safety
+        ///
+        /// Nothing else can be using the channel while the handle exists.
+        /// Normally you should get the handle from `Peripherals::take` instead.
+        pub const unsafe fn new() -> Self {
+          Self { _priv: () }
+        }
+      }
+    )*
+  };
+}
+impl_dma_new!(0, 1, 2, 3);
+
+/// DMA channel 0 (highest priority, internal memory only).
+pub type Dma0 = DmaChannel<0>;
+
+/// DMA channel 1 (usually sound FIFO A).
+pub type Dma1 = DmaChannel<1>;
+
+/// DMA channel 2 (usually sound FIFO B).
+pub type Dma2 = DmaChannel<2>;
+
+/// DMA channel 3 (general purpose).
+pub type Dma3 = DmaChannel<3>;
+
+impl<const N: usize> DmaChannel<N> {
+  /// The address of this channel's source register. Each channel's registers
+  /// are 12 bytes after the previous channel's.
+  const BASE: usize = 0x400_00B0 + N * 12;
+
+  fn control_address() -> VolAddress<DMAControl> {
+    unsafe { VolAddress::new(Self::BASE + 10) }
+  }
+
+  /// Reads the control register.
+  pub fn control(&self) -> DMAControl {
+    Self::control_address().read()
+  }
+
+  /// If the channel is enabled (a repeating transfer stays enabled).
+  pub fn is_enabled(&self) -> bool {
+    self.control().dma_enable()
+  }
+
+  /// Stops the channel, which ends a repeating transfer.
+  pub fn stop(&mut self) {
+    Self::control_address().write(DMAControl::new())
+  }
+
+  /// Validates a transfer and then starts it.
+  ///
+  /// # Safety
+  ///
+  /// The transfer writes to whatever memory it's pointed at, so all of the
+  /// rules for `set_dma0_control` apply.
+  pub unsafe fn start(&mut self, transfer: &DmaTransfer) -> Result<(), DmaError> {
+    transfer.validate(N)?;
+    WOVolAddress::<usize>::new(Self::BASE).write(transfer.source);
+    WOVolAddress::<usize>::new(Self::BASE + 4).write(transfer.dest);
+    WOVolAddress::<u16>::new(Self::BASE + 8).write(transfer.count_register());
+    Self::control_address().write(transfer.control.with_dma_enable(true));
+    Ok(())
+  }
+
+  /// Copies a slice of `u16` values.
+  pub fn copy16(&mut self, src: &[u16], dest: &mut [u16]) -> Result<(), DmaError> {
+    if src.len() != dest.len() {
+      return Err(DmaError::LengthMismatch);
+    }
+    if src.is_empty() {
+      return Ok(());
+    }
+    let transfer =
+      DmaTransfer::new(src.as_ptr() as usize, dest.as_mut_ptr() as usize, src.len() as u32);
+    unsafe { self.start(&transfer) }
+  }
+
+  /// Copies a slice of `u32` values.
+  pub fn copy32(&mut self, src: &[u32], dest: &mut [u32]) -> Result<(), DmaError> {
+    if src.len() != dest.len() {
+      return Err(DmaError::LengthMismatch);
+    }
+    if src.is_empty() {
+      return Ok(());
+    }
+    let transfer =
+      DmaTransfer::new(src.as_ptr() as usize, dest.as_mut_ptr() as usize, src.len() as u32)
+        .with_32bit(true);
+    unsafe { self.start(&transfer) }
+  }
+
+  /// Fills a slice with a `u16` value, using a fixed source address.
+  pub fn fill16(&mut self, value: &u16, dest: &mut [u16]) -> Result<(), DmaError> {
+    if dest.is_empty() {
+      return Ok(());
+    }
+    let transfer =
+      DmaTransfer::new(value as *const u16 as usize, dest.as_mut_ptr() as usize, dest.len() as u32)
+        .with_source_control(SourceAddressControl::Fixed);
+    unsafe { self.start(&transfer) }
+  }
+
+  /// Fills a slice with a `u32` value, using a fixed source address.
+  pub fn fill32(&mut self, value: &u32, dest: &mut [u32]) -> Result<(), DmaError> {
+    if dest.is_empty() {
+      return Ok(());
+    }
+    let transfer =
+      DmaTransfer::new(value as *const u32 as usize, dest.as_mut_ptr() as usize, dest.len() as u32)
+        .with_source_control(SourceAddressControl::Fixed)
+        .with_32bit(true);
+    unsafe { self.start(&transfer) }
+  }
+
+  /// Starts a repeating HBlank transfer, for per-scanline effects.
+  ///
+  /// At the start of each HBlank, `count` units are copied from the next part
+  /// of `src` into `dest`, and `dest` goes back to where it started. This is
+  /// how you change scroll or window registers on every line. Stop the
+  /// channel (or restart it at VBlank) to keep `src` from running off the
+  /// end of its table.
+  ///
+  /// # Safety
+  ///
+  /// `src` must stay valid for as long as the transfer runs, and `dest` must
+  /// be fine to write (usually an IO register).
+  pub unsafe fn hblank_repeat(
+    &mut self, src: *const u32, dest: usize, count: u32, is_32bit: bool,
+  ) -> Result<(), DmaError> {
+    let transfer = DmaTransfer::new(src as usize, dest, count)
+      .with_32bit(is_32bit)
+      .with_dest_control(DestAddressControl::IncrementReload)
+      .with_timing(StartTiming::HBlank)
+      .with_repeat(true);
+    self.start(&transfer)
+  }
+
+  /// Starts feeding a sound FIFO (`FIFO_A` or `FIFO_B`), which only works on
+  /// DMA1 and DMA2.
+  ///
+  /// The sound hardware requests 4 words from `src` each time the FIFO runs
+  /// low, until the channel is stopped.
+  ///
+  /// # Safety
+  ///
+  /// `src` must stay valid for as long as the transfer runs.
+  pub unsafe fn sound_fifo(&mut self, src: *const u32, fifo: usize) -> Result<(), DmaError> {
+    if N != 1 && N != 2 {
+      return Err(DmaError::SpecialTimingNotSupported);
+    }
+    let transfer = DmaTransfer::new(src as usize, fifo, 4)
+      .with_32bit(true)
+      .with_dest_control(DestAddressControl::Fixed)
+      .with_timing(StartTiming::Special)
+      .with_repeat(true);
+    self.start(&transfer)
+  }
+}
@@ -0,0 +1,42 @@
+use super::*;
+use crate::gpio::GpioPins;
+
+/// Cart GPIO pin values.
+pub const GPIO_DATA: VolAddress<GpioData> = unsafe { VolAddress::new(0x800_00C4) };
+
+/// Cart GPIO pin directions.
+pub const GPIO_DIRECTION: VolAddress<GpioDirection> = unsafe { VolAddress::new(0x800_00C6) };
+
+/// Cart GPIO control.
+pub const GPIO_CONTROL: VolAddress<GpioControl> = unsafe { VolAddress::new(0x800_00C8) };
+
+/// [GpioPins] that use the real cart GPIO registers.
+#[derive(Debug)]
+pub struct HardwareGpio {
+  _priv: (),
+}
+impl HardwareGpio {
+  /// Makes the port.
+  ///
+  /// # Safety
+  ///
+  /// There must be only one of these active at a time, and the cart must
+  /// actually have a GPIO port (otherwise the writes go to the ROM).
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+}
+impl GpioPins for HardwareGpio {
+  fn write_data(&mut self, data: GpioData) {
+    GPIO_DATA.write(data)
+  }
+  fn read_data(&mut self) -> GpioData {
+    GPIO_DATA.read()
+  }
+  fn set_direction(&mut self, dir: GpioDirection) {
+    GPIO_DIRECTION.write(dir)
+  }
+  fn set_control(&mut self, ctrl: GpioControl) {
+    GPIO_CONTROL.write(ctrl)
+  }
+}
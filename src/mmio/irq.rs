@@ -45,13 +45,106 @@ pub fn interrupts_are_enabled() -> bool {
   unsafe { core::mem::transmute(IME.read() & 0b1) }
 }
 
+/// Runs the closure with interrupts disabled, then puts `IME` back how it was.
+///
+/// The closure gets a token that proves interrupts are off, which is what
+/// [IrqCell](crate::sync::IrqCell) and [IrqMutex](crate::sync::IrqMutex) need
+/// to be accessed. Keep the closure short, any interrupt that comes in during
+/// it is delayed until it's over.
+pub fn free<R>(f: impl FnOnce(&crate::sync::CriticalSection) -> R) -> R {
+  let were_enabled = interrupts_are_enabled();
+  disable_interrupts();
+  core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+  let out = f(unsafe { &crate::sync::CriticalSection::new() });
+  core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+  if were_enabled {
+    unsafe { enable_interrupts() };
+  }
+  out
+}
+
 /// Interrupt Enable, see the type for info.
 pub const IE: VolAddress<InterruptEnable> = unsafe { VolAddress::new(0x4_000_200) };
 
 /// Check to see what interrupts are pending. Part of GBATEK `IF`.
 pub const IRQ_PENDING: ROVolAddress<InterruptRequestFlags> =
-  unsafe { ROVolAddress::new(0x4_000_200) };
+  unsafe { ROVolAddress::new(0x4_000_202) };
 
 /// Acknowledge an interrupt as processed. Part of GBATEK `IF`.
 pub const IRQ_ACKNOWLEDGE: WOVolAddress<InterruptRequestFlags> =
-  unsafe { WOVolAddress::new(0x4_000_200) };
+  unsafe { WOVolAddress::new(0x4_000_202) };
+
+/// The BIOS's copy of the interrupt flags, GBATEK `IntrCheck`.
+///
+/// The `IntrWait` and `VBlankIntrWait` BIOS calls wait for bits to show up
+/// here, so an interrupt handler has to set them along with acknowledging
+/// `IF`.
+pub const BIOS_IRQ_CHECK: VolAddress<u16> = unsafe { VolAddress::new(0x300_7FF8) };
+
+/// Where the BIOS looks for the user interrupt handler.
+///
+/// The BIOS calls the handler in ARM state, using an `ldr pc`, so the address
+/// must be of ARM code.
+pub const BIOS_IRQ_HANDLER: VolAddress<usize> = unsafe { VolAddress::new(0x300_7FFC) };
+
+/// The interrupt control registers.
+///
+/// There's only one, which you get from `Peripherals::take`.
+#[derive(Debug)]
+pub struct Irq {
+  _priv: (),
+}
+impl Irq {
+  /// Makes a handle to the interrupt control registers.
+  ///
+  /// # Safety
+  ///
+  /// Nothing else can be using `IE` or `IF` while the handle exists. Normally
+  /// you should get the handle from `Peripherals::take` instead.
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+
+  /// Reads `IE`.
+  pub fn enabled(&self) -> InterruptEnable {
+    IE.read()
+  }
+
+  /// Writes `IE`.
+  pub fn set_enabled(&mut self, enabled: InterruptEnable) {
+    IE.write(enabled)
+  }
+
+  /// Reads the pending interrupts.
+  pub fn pending(&self) -> InterruptRequestFlags {
+    IRQ_PENDING.read()
+  }
+
+  /// Acknowledges interrupts, in both `IF` and the BIOS mirror.
+  ///
+  /// Interrupts are disabled while the BIOS mirror is updated, so that a
+  /// handler setting its own bit in between can't have it overwritten.
+  pub fn acknowledge(&mut self, flags: InterruptRequestFlags) {
+    IRQ_ACKNOWLEDGE.write(flags);
+    free(|_| BIOS_IRQ_CHECK.write(BIOS_IRQ_CHECK.read() | flags.bits()));
+  }
+
+  /// Turns on `IME`, see [enable_interrupts].
+  ///
+  /// # Safety
+  ///
+  /// The same as [enable_interrupts].
+  pub unsafe fn enable_master(&mut self) {
+    enable_interrupts()
+  }
+
+  /// Turns off `IME`.
+  pub fn disable_master(&mut self) {
+    disable_interrupts()
+  }
+}
+
+#[cfg(feature = "irq_runtime")]
+mod runtime;
+#[cfg(feature = "irq_runtime")]
+pub use runtime::*;
@@ -0,0 +1,175 @@
+//! The master interrupt handler, enabled with the `irq_runtime` feature.
+//!
+//! Call [install_irq_dispatcher] once, register handlers with
+//! [set_irq_handler], then turn on the interrupts you want in `IE` and finally
+//! [enable_interrupts].
+//!
+//! ## Linking
+//!
+//! The BIOS jumps to the handler in ARM state, and ARM code is slow to run
+//! from ROM, so the trampoline is put in the `.iwram.gba_hal_irq_trampoline`
+//! section. Your linker script has to place `.iwram.*` sections in IWRAM (and
+//! your startup code has to copy them there), the same as for any other IWRAM
+//! code.
+//!
+//! ## Stack
+//!
+//! With nesting off (the default), handlers run in IRQ mode on the BIOS IRQ
+//! stack. That's only 160 bytes (`0x300_7F00` to `0x300_7FA0`), and the BIOS
+//! and the trampoline use 32 of them, so handlers need to keep their locals
+//! and call depth small. Going past the end silently overwrites the top of the
+//! main program's stack. With [set_irq_nesting] on, handlers run on the main
+//! program's stack instead.
+
+use super::*;
+use crate::{
+  bus::{RegisterBus, Width},
+  irq::{acknowledge, IrqDispatcher},
+};
+
+/// The handlers that the master interrupt handler dispatches to.
+static mut IRQ_DISPATCHER: IrqDispatcher = IrqDispatcher::new();
+
+/// Non-zero if handlers should run with interrupts enabled. Read by the
+/// trampoline.
+#[no_mangle]
+static mut GBA_HAL_IRQ_NESTING: u8 = 0;
+
+extern "C" {
+  /// The ARM code that the BIOS calls, defined below.
+  fn gba_hal_irq_trampoline();
+}
+
+/// Direct access for [acknowledge], which only uses `IE`, `IF`, and the BIOS
+/// copy of `IF`.
+struct IrqRegisters;
+impl RegisterBus for IrqRegisters {
+  fn read(&mut self, address: usize, width: Width) -> u32 {
+    unsafe {
+      match width {
+        Width::U8 => u32::from(VolAddress::<u8>::new(address).read()),
+        Width::U16 => u32::from(VolAddress::<u16>::new(address).read()),
+        Width::U32 => VolAddress::<u32>::new(address).read(),
+      }
+    }
+  }
+
+  fn write(&mut self, address: usize, width: Width, value: u32) {
+    unsafe {
+      match width {
+        Width::U8 => VolAddress::<u8>::new(address).write(value as u8),
+        Width::U16 => VolAddress::<u16>::new(address).write(value as u16),
+        Width::U32 => VolAddress::<u32>::new(address).write(value),
+      }
+    }
+  }
+}
+
+/// Acknowledges the pending interrupts, in both `IF` and the BIOS mirror.
+///
+/// Runs in IRQ mode, before any nesting is allowed, so that an acknowledged
+/// interrupt can't immediately fire again.
+#[no_mangle]
+extern "C" fn gba_hal_irq_acknowledge() -> u16 {
+  acknowledge(&mut IrqRegisters).bits()
+}
+
+/// Calls the registered handlers for the interrupts acknowledged above.
+///
+/// This is the same as `IrqDispatcher::dispatch`, but each handler is looked
+/// up through its own short borrow of the table rather than a copy (which
+/// would have to fit on the IRQ stack), so a handler can still call
+/// [set_irq_handler].
+#[no_mangle]
+extern "C" fn gba_hal_irq_dispatch(pending: u16) {
+  for irq in Interrupt::ALL.iter() {
+    if pending & irq.mask() != 0 {
+      if let Some(handler) = unsafe { (*core::ptr::addr_of!(IRQ_DISPATCHER)).handler(*irq) } {
+        handler();
+      }
+    }
+  }
+}
+
+// The BIOS has already saved r0-r3, r12 and lr on the IRQ stack. We save spsr
+// and lr as well, so that a nested interrupt can't clobber them, then call the
+// Rust code (which may be Thumb, hence the `bx`). With nesting on, the
+// handlers run in System mode with IRQs enabled, on the user stack.
+//
+// This text goes to the assembler as is, so the braces aren't escaped.
+#[cfg(target_arch = "arm")]
+global_asm!(
+  r#"
+  .section .iwram.gba_hal_irq_trampoline, "ax", %progbits
+  .arm
+  .align 2
+  .global gba_hal_irq_trampoline
+gba_hal_irq_trampoline:
+  mrs r1, spsr
+  stmfd sp!, {r1, lr}
+  ldr r1, =gba_hal_irq_acknowledge
+  mov lr, pc
+  bx r1
+  ldr r1, =GBA_HAL_IRQ_NESTING
+  ldrb r1, [r1]
+  cmp r1, #0
+  beq 1f
+  mrs r2, cpsr
+  bic r2, r2, #0xDF
+  orr r2, r2, #0x1F
+  msr cpsr_c, r2
+  stmfd sp!, {r4, lr}
+  ldr r1, =gba_hal_irq_dispatch
+  mov lr, pc
+  bx r1
+  ldmfd sp!, {r4, lr}
+  mrs r2, cpsr
+  bic r2, r2, #0xDF
+  orr r2, r2, #0x92
+  msr cpsr_c, r2
+  b 2f
+1:
+  ldr r1, =gba_hal_irq_dispatch
+  mov lr, pc
+  bx r1
+2:
+  ldmfd sp!, {r1, lr}
+  msr spsr, r1
+  bx lr
+  .pool
+"#
+);
+
+/// Points the BIOS at this crate's master interrupt handler.
+///
+/// # Safety
+///
+/// The master handler takes over _all_ interrupts, so this can't be mixed with
+/// some other interrupt handler. The handlers you register run with the same
+/// caveats as described on [enable_interrupts].
+pub unsafe fn install_irq_dispatcher() {
+  BIOS_IRQ_HANDLER.write(gba_hal_irq_trampoline as unsafe extern "C" fn() as usize);
+}
+
+/// Sets (or clears) the handler for an interrupt, giving back the old one.
+///
+/// Interrupts are disabled while the table is updated. This doesn't touch
+/// `IE`, you still have to enable the interrupt there (and in the device's own
+/// control register).
+pub fn set_irq_handler(irq: Interrupt, handler: Option<fn()>) -> Option<fn()> {
+  free(|_| unsafe { (*core::ptr::addr_of_mut!(IRQ_DISPATCHER)).set_handler(irq, handler) })
+}
+
+/// The interrupts that currently have a handler, ready to write to `IE`.
+pub fn irq_handlers_set() -> InterruptEnable {
+  unsafe { &*core::ptr::addr_of!(IRQ_DISPATCHER) }.handled()
+}
+
+/// Sets if handlers run with interrupts enabled.
+///
+/// When this is on, a handler can itself be interrupted by any interrupt
+/// enabled in `IE` (including another of its own kind), so handlers must be
+/// reentrant and quick. It's off by default.
+pub fn set_irq_nesting(nested: bool) {
+  unsafe { GBA_HAL_IRQ_NESTING = nested as u8 };
+}
@@ -1,8 +1,48 @@
 
 use super::*;
+use crate::input::KeySource;
 
 /// Reads the low-active key values (see [KeyInput](gba_hal::data::KeyInput)).
 pub const KEYINPUT: ROVolAddress<KeyInput> = unsafe { ROVolAddress::new(0x4_000_130) };
 
 /// Key interrupt control.
-pub const KEYCNT: VolAddress<KeyControl> = unsafe { VolAddress::new(0x4_000_130) };
+pub const KEYCNT: VolAddress<KeyControl> = unsafe { VolAddress::new(0x4_000_132) };
+
+/// The keypad registers.
+///
+/// There's only one, which you get from `Peripherals::take`.
+#[derive(Debug)]
+pub struct Keypad {
+  _priv: (),
+}
+impl Keypad {
+  /// Makes a handle to the keypad registers.
+  ///
+  /// # Safety
+  ///
+  /// Nothing else can be using `KEYCNT` while the handle exists. Normally you
+  /// should get the handle from `Peripherals::take` instead.
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+
+  /// Reads the keys that are pressed.
+  pub fn keys(&self) -> Keys {
+    Keys::from_key_input(KEYINPUT.read())
+  }
+
+  /// Reads `KEYCNT`.
+  pub fn key_control(&self) -> KeyControl {
+    KEYCNT.read()
+  }
+
+  /// Writes `KEYCNT`.
+  pub fn set_key_control(&mut self, ctrl: KeyControl) {
+    KEYCNT.write(ctrl)
+  }
+}
+impl KeySource for Keypad {
+  fn read_keys(&mut self) -> KeyInput {
+    KEYINPUT.read()
+  }
+}
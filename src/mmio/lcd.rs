@@ -118,3 +118,101 @@ pub const BLDALPHA: VolAddress<BlendAlpha> = unsafe { VolAddress::new(0x400_0052
 
 /// Configures brightness blending.
 pub const BLDY: WOVolAddress<BlendBrightness> = unsafe { WOVolAddress::new(0x400_0054) };
+
+/// The display registers.
+///
+/// There's only one, which you get from `Peripherals::take`.
+#[derive(Debug)]
+pub struct Lcd {
+  _priv: (),
+}
+impl Lcd {
+  /// Makes a handle to the display registers.
+  ///
+  /// # Safety
+  ///
+  /// Nothing else can be using the display registers while the handle exists.
+  /// Normally you should get the handle from `Peripherals::take` instead.
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+
+  /// Reads `DISPCNT`.
+  pub fn display_control(&self) -> DisplayControl {
+    DISPCNT.read()
+  }
+
+  /// Writes `DISPCNT`.
+  pub fn set_display_control(&mut self, ctrl: DisplayControl) {
+    DISPCNT.write(ctrl)
+  }
+
+  /// Reads `DISPSTAT`.
+  pub fn display_status(&self) -> DisplayStatus {
+    DISPSTAT.read()
+  }
+
+  /// Writes `DISPSTAT`. The read only fields are ignored.
+  pub fn set_display_status(&mut self, status: DisplayStatus) {
+    DISPSTAT.write(status)
+  }
+
+  /// Reads `VCOUNT`.
+  pub fn vcount(&self) -> u16 {
+    VCOUNT.read()
+  }
+
+  /// Reads the control of background 0 through 3.
+  ///
+  /// # Panics
+  ///
+  /// If the background is 4 or more.
+  pub fn bg_control(&self, bg: usize) -> BackgroundControl {
+    assert!(bg < 4, "background index out of range");
+    unsafe { VolAddress::<BackgroundControl>::new(BG0CNT.to_usize() + bg * 2) }.read()
+  }
+
+  /// Writes the control of background 0 through 3.
+  ///
+  /// # Panics
+  ///
+  /// If the background is 4 or more.
+  pub fn set_bg_control(&mut self, bg: usize, ctrl: BackgroundControl) {
+    assert!(bg < 4, "background index out of range");
+    unsafe { VolAddress::<BackgroundControl>::new(BG0CNT.to_usize() + bg * 2) }.write(ctrl)
+  }
+
+  /// Sets the scroll offset of background 0 through 3 (only used in text
+  /// mode).
+  ///
+  /// # Panics
+  ///
+  /// If the background is 4 or more.
+  pub fn set_bg_offset(&mut self, bg: usize, x: u16, y: u16) {
+    assert!(bg < 4, "background index out of range");
+    unsafe {
+      WOVolAddress::<u16>::new(0x400_0010 + bg * 4).write(x);
+      WOVolAddress::<u16>::new(0x400_0012 + bg * 4).write(y);
+    }
+  }
+
+  /// Writes `MOSAIC`.
+  pub fn set_mosaic(&mut self, mosaic: Mosaic) {
+    MOSAIC.write(mosaic)
+  }
+
+  /// Writes `BLDCNT`.
+  pub fn set_blend_control(&mut self, ctrl: BlendControl) {
+    BLDCNT.write(ctrl)
+  }
+
+  /// Writes `BLDALPHA`.
+  pub fn set_blend_alpha(&mut self, alpha: BlendAlpha) {
+    BLDALPHA.write(alpha)
+  }
+
+  /// Writes `BLDY`.
+  pub fn set_blend_brightness(&mut self, brightness: BlendBrightness) {
+    BLDY.write(brightness)
+  }
+}
@@ -0,0 +1,29 @@
+use super::*;
+use crate::{dma::DmaTransfer, oam::ShadowOam};
+
+/// All of OAM as `u32` words.
+pub const OAM_WORDS: VolBlock<u32, U256> = unsafe { VolBlock::new(0x700_0000) };
+
+impl ShadowOam {
+  /// Copies the whole buffer into OAM with word writes.
+  ///
+  /// Call this during VBlank (or while `force_vblank` is set), since OAM
+  /// changes during drawing will show up partway through the frame.
+  pub fn commit(&self) {
+    for (addr, word) in OAM_WORDS.iter().zip(self.as_words().iter()) {
+      addr.write(*word);
+    }
+  }
+
+  /// Copies the whole buffer into OAM with a single DMA3 transfer.
+  ///
+  /// This is faster than [commit](ShadowOam::commit), but it replaces whatever
+  /// DMA3 was set up to do, which is why it needs the channel handle. As with
+  /// `commit`, call this during VBlank.
+  pub fn commit_dma3(&self, dma: &mut Dma3) {
+    let transfer =
+      DmaTransfer::new(self.as_words().as_ptr() as usize, OAM_WORDS.index(0).to_usize(), 256)
+        .with_32bit(true);
+    unsafe { dma.start(&transfer) }.expect("word aligned RAM to OAM is a valid DMA3 transfer");
+  }
+}
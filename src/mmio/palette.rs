@@ -0,0 +1,131 @@
+use super::*;
+use crate::palette::PaletteBuffer;
+use core::ops::Range;
+use typenum::consts::U16;
+
+/// One 16 color palbank, as used by 4bpp tiles.
+///
+/// This is the same `palbank` index as in `OBJAttr2` and `TextScreenEntry`.
+/// Color 0 of each palbank is transparent (and color 0 of background palbank 0
+/// is the backdrop color).
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteBank {
+  block: VolBlock<Color, U16>,
+}
+impl PaletteBank {
+  /// Background palbank `n` (within `PALRAM_BG`).
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 16 or more.
+  pub fn bg(n: usize) -> Self {
+    assert!(n < 16, "palbank index out of range");
+    Self { block: unsafe { VolBlock::new(PALRAM_BG.index(n * 16).to_usize()) } }
+  }
+
+  /// Object palbank `n` (within `PALRAM_OBJ`).
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 16 or more.
+  pub fn obj(n: usize) -> Self {
+    assert!(n < 16, "palbank index out of range");
+    Self { block: unsafe { VolBlock::new(PALRAM_OBJ.index(n * 16).to_usize()) } }
+  }
+
+  /// The palbank's colors.
+  pub const fn block(self) -> VolBlock<Color, U16> {
+    self.block
+  }
+
+  /// Reads color `i` (0 to 15).
+  ///
+  /// # Panics
+  ///
+  /// If `i` is 16 or more.
+  pub fn read(self, i: usize) -> Color {
+    self.block.index(i).read()
+  }
+
+  /// Writes color `i` (0 to 15).
+  ///
+  /// # Panics
+  ///
+  /// If `i` is 16 or more.
+  pub fn write(self, i: usize, color: Color) {
+    self.block.index(i).write(color)
+  }
+
+  /// Writes colors starting at color 0. Any colors past 16 are ignored.
+  pub fn load(self, colors: &[Color]) {
+    for (addr, color) in self.block.iter().zip(colors.iter()) {
+      addr.write(*color);
+    }
+  }
+
+  /// Reads all 16 colors.
+  pub fn to_array(self) -> [Color; 16] {
+    let mut colors = [Color::BLACK; 16];
+    for (color, addr) in colors.iter_mut().zip(self.block.iter()) {
+      *color = addr.read();
+    }
+    colors
+  }
+
+  /// Rotates the colors in a range `n` places towards the start, wrapping the
+  /// first colors around to the end. This is the usual "color cycling"
+  /// effect.
+  ///
+  /// # Panics
+  ///
+  /// If the range isn't within 0 to 16, or `n` is more than the range's
+  /// length.
+  pub fn rotate_left(self, range: Range<usize>, n: usize) {
+    let mut colors = self.to_array();
+    colors[range.clone()].rotate_left(n);
+    for i in range {
+      self.write(i, colors[i]);
+    }
+  }
+
+  /// Rotates the colors in a range `n` places towards the end, wrapping the
+  /// last colors around to the start.
+  ///
+  /// # Panics
+  ///
+  /// If the range isn't within 0 to 16, or `n` is more than the range's
+  /// length.
+  pub fn rotate_right(self, range: Range<usize>, n: usize) {
+    let mut colors = self.to_array();
+    colors[range.clone()].rotate_right(n);
+    for i in range {
+      self.write(i, colors[i]);
+    }
+  }
+}
+
+impl PaletteBuffer {
+  /// Copies the whole buffer into palette RAM.
+  ///
+  /// Call this during VBlank (or while `force_vblank` is set) so that the
+  /// colors don't change partway through a frame.
+  pub fn commit(&self) {
+    for (addr, color) in PALRAM_BG.iter().zip(self.bg.iter()) {
+      addr.write(*color);
+    }
+    for (addr, color) in PALRAM_OBJ.iter().zip(self.obj.iter()) {
+      addr.write(*color);
+    }
+  }
+
+  /// Commits the buffer with every color moved towards a target color,
+  /// without changing the buffer itself (see `faded_to`).
+  pub fn commit_faded_to(&self, target: Color, t: u16) {
+    for (addr, color) in PALRAM_BG.iter().zip(self.bg.iter()) {
+      addr.write(color.lerp(target, t));
+    }
+    for (addr, color) in PALRAM_OBJ.iter().zip(self.obj.iter()) {
+      addr.write(color.lerp(target, t));
+    }
+  }
+}
@@ -0,0 +1,77 @@
+use super::*;
+
+/// Set once the peripherals have been taken.
+static mut TAKEN: bool = false;
+
+/// Unique handles to the hardware's register groups.
+///
+/// The constants in this module can be used from anywhere, so nothing stops
+/// two parts of a program from fighting over the same registers. The handles
+/// here can only be had once each, and the safe higher level methods are on
+/// them. Split the struct up and pass each handle to whatever owns that
+/// hardware. The raw constants are still there when you need to get around
+/// this.
+#[derive(Debug)]
+pub struct Peripherals {
+  /// The display registers.
+  pub lcd: Lcd,
+  /// The sound control registers.
+  pub sound: Sound,
+  /// DMA channel 0.
+  pub dma0: Dma0,
+  /// DMA channel 1.
+  pub dma1: Dma1,
+  /// DMA channel 2.
+  pub dma2: Dma2,
+  /// DMA channel 3.
+  pub dma3: Dma3,
+  /// Timer 0.
+  pub timer0: Timer0,
+  /// Timer 1.
+  pub timer1: Timer1,
+  /// Timer 2.
+  pub timer2: Timer2,
+  /// Timer 3.
+  pub timer3: Timer3,
+  /// The keypad registers.
+  pub keypad: Keypad,
+  /// The interrupt control registers.
+  pub irq: Irq,
+}
+impl Peripherals {
+  /// Gets the peripherals, the first time this is called. After that it gives
+  /// `None`.
+  pub fn take() -> Option<Self> {
+    free(|_| unsafe {
+      if TAKEN {
+        None
+      } else {
+        TAKEN = true;
+        Some(Self::steal())
+      }
+    })
+  }
+
+  /// Gets the peripherals no matter what.
+  ///
+  /// # Safety
+  ///
+  /// This makes new handles even if the old ones are still around, so you
+  /// have to be sure that they're not used at the same time.
+  pub unsafe fn steal() -> Self {
+    Self {
+      lcd: Lcd::new(),
+      sound: Sound::new(),
+      dma0: Dma0::new(),
+      dma1: Dma1::new(),
+      dma2: Dma2::new(),
+      dma3: Dma3::new(),
+      timer0: Timer0::new(),
+      timer1: Timer1::new(),
+      timer2: Timer2::new(),
+      timer3: Timer3::new(),
+      keypad: Keypad::new(),
+      irq: Irq::new(),
+    }
+  }
+}
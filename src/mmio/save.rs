@@ -0,0 +1,78 @@
+use super::*;
+use crate::{
+  dma::DmaTransfer,
+  save::{EepromBus, SaveBus},
+};
+use typenum::consts::U65536;
+
+/// The SRAM / Flash region of the cart.
+///
+/// This region has an 8-bit bus, so only `u8` access works correctly.
+pub const SAVE_RAM: VolBlock<u8, U65536> = unsafe { VolBlock::new(0xE00_0000) };
+
+/// The address that the EEPROM is accessed through.
+///
+/// On carts of 16MB or less the EEPROM is mapped over all of `0xD00_0000`
+/// through `0xDFF_FFFF`, and this is the start of that range.
+pub const EEPROM_ADDRESS: usize = 0xD00_0000;
+
+/// A [SaveBus] that uses the real SRAM / Flash region.
+#[derive(Debug)]
+pub struct HardwareSaveBus {
+  _priv: (),
+}
+impl HardwareSaveBus {
+  /// Makes the bus.
+  ///
+  /// # Safety
+  ///
+  /// There must be only one of these active at a time, and the cart must
+  /// actually use SRAM or Flash.
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+}
+impl SaveBus for HardwareSaveBus {
+  fn read(&mut self, offset: usize) -> u8 {
+    SAVE_RAM.index(offset).read()
+  }
+  fn write(&mut self, offset: usize, value: u8) {
+    SAVE_RAM.index(offset).write(value)
+  }
+}
+
+/// An [EepromBus] that transfers with DMA3, as the EEPROM requires.
+///
+/// The bus borrows the [Dma3] handle, so nothing else can start a DMA3
+/// transfer while it's in use.
+#[derive(Debug)]
+pub struct DmaEepromBus<'a> {
+  dma: &'a mut Dma3,
+}
+impl<'a> DmaEepromBus<'a> {
+  /// Makes the bus.
+  ///
+  /// # Safety
+  ///
+  /// The cart must actually use EEPROM. An interrupt handler must not be
+  /// using DMA3 either, since it doesn't go through the handle.
+  pub unsafe fn new(dma: &'a mut Dma3) -> Self {
+    Self { dma }
+  }
+
+  /// Runs one immediate 16-bit DMA3 transfer.
+  fn transfer(&mut self, src: usize, dest: usize, count: usize) {
+    // EEPROM requests are never empty, and both ends are halfword aligned and
+    // in range for DMA3.
+    unsafe { self.dma.start(&DmaTransfer::new(src, dest, count as u32)) }
+      .expect("EEPROM requests are valid DMA3 transfers");
+  }
+}
+impl EepromBus for DmaEepromBus<'_> {
+  fn send(&mut self, bits: &[u16]) {
+    self.transfer(bits.as_ptr() as usize, EEPROM_ADDRESS, bits.len())
+  }
+  fn receive(&mut self, bits: &mut [u16]) {
+    self.transfer(EEPROM_ADDRESS, bits.as_mut_ptr() as usize, bits.len())
+  }
+}
@@ -0,0 +1,46 @@
+use super::*;
+
+/// The 32-bit data for Normal 32-bit mode. GBATEK `SIODATA32`.
+pub const SIODATA32: VolAddress<u32> = unsafe { VolAddress::new(0x400_0120) };
+
+/// Multi-player data from the parent. GBATEK `SIOMULTI0`.
+pub const SIOMULTI0: VolAddress<u16> = unsafe { VolAddress::new(0x400_0120) };
+
+/// Multi-player data from the 1st child. GBATEK `SIOMULTI1`.
+pub const SIOMULTI1: VolAddress<u16> = unsafe { VolAddress::new(0x400_0122) };
+
+/// Multi-player data from the 2nd child. GBATEK `SIOMULTI2`.
+pub const SIOMULTI2: VolAddress<u16> = unsafe { VolAddress::new(0x400_0124) };
+
+/// Multi-player data from the 3rd child. GBATEK `SIOMULTI3`.
+pub const SIOMULTI3: VolAddress<u16> = unsafe { VolAddress::new(0x400_0126) };
+
+/// Serial control, as seen in Normal mode. GBATEK `SIOCNT`.
+pub const SIOCNT: VolAddress<SioNormalControl> = unsafe { VolAddress::new(0x400_0128) };
+
+/// Serial control, as seen in Multi-player mode. GBATEK `SIOCNT`.
+pub const SIOCNT_MULTI: VolAddress<SioMultiControl> = unsafe { VolAddress::new(0x400_0128) };
+
+/// Serial control, as seen in UART mode. GBATEK `SIOCNT`.
+pub const SIOCNT_UART: VolAddress<SioUartControl> = unsafe { VolAddress::new(0x400_0128) };
+
+/// The data to send in Multi-player mode. GBATEK `SIOMLT_SEND`.
+pub const SIOMLT_SEND: VolAddress<u16> = unsafe { VolAddress::new(0x400_012A) };
+
+/// The 8-bit data for Normal 8-bit and UART modes. GBATEK `SIODATA8`.
+pub const SIODATA8: VolAddress<u8> = unsafe { VolAddress::new(0x400_012A) };
+
+/// Link port mode selection. GBATEK `RCNT`.
+pub const RCNT: VolAddress<LinkPortControl> = unsafe { VolAddress::new(0x400_0134) };
+
+/// JOY Bus control. GBATEK `JOYCNT`.
+pub const JOYCNT: VolAddress<JoyBusControl> = unsafe { VolAddress::new(0x400_0140) };
+
+/// Data received over the JOY Bus. GBATEK `JOY_RECV`.
+pub const JOY_RECV: VolAddress<u32> = unsafe { VolAddress::new(0x400_0150) };
+
+/// Data to send over the JOY Bus. GBATEK `JOY_TRANS`.
+pub const JOY_TRANS: VolAddress<u32> = unsafe { VolAddress::new(0x400_0154) };
+
+/// JOY Bus status. GBATEK `JOYSTAT`.
+pub const JOYSTAT: VolAddress<JoyBusStatus> = unsafe { VolAddress::new(0x400_0158) };
@@ -89,3 +89,62 @@ pub const FIFO_A: WOVolAddress<[i8; 4]> = unsafe { WOVolAddress::new(0x400_00A0)
 ///
 /// As `FIFO_A`, but for DMA Sound B
 pub const FIFO_B: WOVolAddress<[i8; 4]> = unsafe { WOVolAddress::new(0x400_00A4) };
+
+/// The sound control registers.
+///
+/// There's only one, which you get from `Peripherals::take`.
+#[derive(Debug)]
+pub struct Sound {
+  _priv: (),
+}
+impl Sound {
+  /// Makes a handle to the sound control registers.
+  ///
+  /// # Safety
+  ///
+  /// Nothing else can be using the sound registers while the handle exists.
+  /// Normally you should get the handle from `Peripherals::take` instead.
+  pub const unsafe fn new() -> Self {
+    Self { _priv: () }
+  }
+
+  /// Reads `SOUND_STATUS_ENABLE`.
+  pub fn status(&self) -> SoundStatusMaster {
+    SOUND_STATUS_ENABLE.read()
+  }
+
+  /// Turns all sound on or off.
+  pub fn set_enabled(&mut self, enabled: bool) {
+    SOUND_STATUS_ENABLE.write(SoundStatusMaster::new().with_sound_enabled(enabled))
+  }
+
+  /// Reads `STEREO_CONTROL`.
+  pub fn stereo_control(&self) -> StereoControl {
+    STEREO_CONTROL.read()
+  }
+
+  /// Writes `STEREO_CONTROL`.
+  pub fn set_stereo_control(&mut self, ctrl: StereoControl) {
+    STEREO_CONTROL.write(ctrl)
+  }
+
+  /// Reads `DMA_MIXER`.
+  pub fn dma_mixer(&self) -> DMAMixer {
+    DMA_MIXER.read()
+  }
+
+  /// Writes `DMA_MIXER`.
+  pub fn set_dma_mixer(&mut self, mixer: DMAMixer) {
+    DMA_MIXER.write(mixer)
+  }
+
+  /// Reads `SOUNDBIAS`.
+  pub fn bias(&self) -> Soundbias {
+    SOUNDBIAS.read()
+  }
+
+  /// Writes `SOUNDBIAS`.
+  pub fn set_bias(&mut self, bias: Soundbias) {
+    SOUNDBIAS.write(bias)
+  }
+}
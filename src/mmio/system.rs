@@ -0,0 +1,23 @@
+use super::*;
+
+/// Game pak waitstate control. GBATEK `WAITCNT`.
+pub const WAITCNT: VolAddress<WaitstateControl> = unsafe { VolAddress::new(0x400_0204) };
+
+/// Post boot flag. GBATEK `POSTFLG`.
+///
+/// The BIOS sets bit 0 once it has finished the boot sequence, so this is 0
+/// only on a fresh power on.
+pub const POSTFLG: VolAddress<u8> = unsafe { VolAddress::new(0x400_0300) };
+
+/// Enters a low power mode. GBATEK `HALTCNT`.
+///
+/// Generally you want to use the BIOS `Halt`, `Stop`, or `IntrWait` functions
+/// instead of writing this directly.
+pub const HALTCNT: WOVolAddress<HaltControl> = unsafe { WOVolAddress::new(0x400_0301) };
+
+/// Undocumented internal memory control.
+///
+/// This is mirrored every 64k across the entire IO region, but is only listed
+/// here at its base address.
+pub const INTERNAL_MEMORY_CONTROL: VolAddress<InternalMemoryControl> =
+  unsafe { VolAddress::new(0x400_0800) };
@@ -35,3 +35,256 @@ pub const TIMER2_CONTROL: VolAddress<TimerControl> = unsafe { VolAddress::new(0x
 
 /// Controls the actions of Timer3. GBATEK `TM3CNT_H`.
 pub const TIMER3_CONTROL: VolAddress<TimerControl> = unsafe { VolAddress::new(0x4_000_10E) };
+
+/// A handle to one of the four timers.
+///
+/// There's only one of each timer, which you get from `Peripherals::take`.
+/// Only timers 0 through 3 exist, so those are the only ones with a `new`:
+///
+/// ```compile_fail
+/// let timer4 = unsafe { gba_hal::mmio::Timer::<4>::new() };
+/// ```
+#[derive(Debug)]
+pub struct Timer<const N: usize> {
+  _priv: (),
+}
+
+macro_rules! impl_timer_new {
+  ($($n:literal),*) => {
+    $(
+      impl Timer<$n> {
+        /// Makes a handle to the timer.
+        ///
+        /// # Safety
+        ///
+        /// Nothing else can be using the timer while the handle exists.
+        /// Normally you should get the handle from `Peripherals::take` instead.
+        pub const unsafe fn new() -> Self {
+          Self { _priv: () }
+        }
+      }
+    )*
+  };
+}
+impl_timer_new!(0, 1, 2, 3);
+
+/// Timer 0
+pub type Timer0 = Timer<0>;
+
+/// Timer 1
+pub type Timer1 = Timer<1>;
+
+/// Timer 2
+pub type Timer2 = Timer<2>;
+
+/// Timer 3
+pub type Timer3 = Timer<3>;
+
+impl<const N: usize> Timer<N> {
+  /// The address of this timer's counter/reload register. Each timer's
+  /// registers are 4 bytes after the previous timer's.
+  const BASE: usize = 0x400_0100 + N * 4;
+
+  /// Reads the current counter value.
+  pub fn counter(&self) -> u16 {
+    unsafe { ROVolAddress::<u16>::new(Self::BASE) }.read()
+  }
+
+  /// Sets the reload value, which is used the next time the timer starts or
+  /// overflows.
+  pub fn set_reload(&mut self, reload: u16) {
+    unsafe { WOVolAddress::<u16>::new(Self::BASE) }.write(reload)
+  }
+
+  /// Reads the control register.
+  pub fn control(&self) -> TimerControl {
+    unsafe { VolAddress::<TimerControl>::new(Self::BASE + 2) }.read()
+  }
+
+  /// Writes the control register.
+  pub fn set_control(&mut self, ctrl: TimerControl) {
+    unsafe { VolAddress::<TimerControl>::new(Self::BASE + 2) }.write(ctrl)
+  }
+
+  /// If the timer is running.
+  pub fn is_running(&self) -> bool {
+    self.control().enabled()
+  }
+
+  /// Starts the timer counting at a tick rate, from the reload value.
+  ///
+  /// If the timer was already running it restarts.
+  pub fn start(&mut self, rate: TimerTickRate) {
+    self.stop();
+    self.set_control(TimerControl::new().with_tick_rate(rate).with_enabled(true));
+  }
+
+  /// Starts the timer ticking once each time the timer below it overflows,
+  /// from the reload value. This does nothing useful for Timer 0.
+  pub fn start_cascade(&mut self) {
+    self.stop();
+    self.set_control(TimerControl::new().with_cascade(true).with_enabled(true));
+  }
+
+  /// Stops the timer, keeping the current count.
+  pub fn stop(&mut self) {
+    self.set_control(self.control().with_enabled(false));
+  }
+
+  /// Restarts the timer from the reload value, keeping the other settings.
+  pub fn reload(&mut self) {
+    let ctrl = self.control();
+    self.set_control(ctrl.with_enabled(false));
+    self.set_control(ctrl.with_enabled(true));
+  }
+}
+
+/// Timers `N` and `N+1` cascaded into one 32-bit counter.
+///
+/// Timer `N` ticks at the chosen rate, and timer `N+1` ticks each time that
+/// timer `N` overflows, so together they count up to `u32::MAX`.
+///
+/// Make one with `cascade` on the lower timer. `N` names the lower timer, so
+/// only 0 through 2 can be made (Timer 3 has nothing above it):
+///
+/// ```compile_fail
+/// # use gba_hal::mmio::*;
+/// fn pair(timer3: Timer3, timer4: Timer<4>) -> CascadeTimer<3> {
+///   timer3.cascade(timer4)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CascadeTimer<const N: usize> {
+  _priv: (),
+}
+
+macro_rules! impl_cascade_pair {
+  ($low:literal, $high:literal) => {
+    impl Timer<$low> {
+      /// Pairs this timer with the one above it as a 32-bit counter.
+      pub fn cascade(self, _high: Timer<$high>) -> CascadeTimer<$low> {
+        CascadeTimer { _priv: () }
+      }
+    }
+    impl CascadeTimer<$low> {
+      /// Gives back the two timers.
+      pub fn split(self) -> (Timer<$low>, Timer<$high>) {
+        (Timer { _priv: () }, Timer { _priv: () })
+      }
+    }
+  };
+}
+impl_cascade_pair!(0, 1);
+impl_cascade_pair!(1, 2);
+impl_cascade_pair!(2, 3);
+
+impl<const N: usize> CascadeTimer<N> {
+  fn low_base() -> usize {
+    0x400_0100 + N * 4
+  }
+
+  fn high_base() -> usize {
+    0x400_0100 + (N + 1) * 4
+  }
+
+  fn low_counter() -> u16 {
+    unsafe { ROVolAddress::<u16>::new(Self::low_base()) }.read()
+  }
+
+  fn high_counter() -> u16 {
+    unsafe { ROVolAddress::<u16>::new(Self::high_base()) }.read()
+  }
+
+  /// Writes the high control first, so that it's already counting (or
+  /// already stopped) when the low timer starts.
+  fn start_controls(low: TimerControl, high: TimerControl) {
+    unsafe {
+      VolAddress::<TimerControl>::new(Self::high_base() + 2).write(high);
+      VolAddress::<TimerControl>::new(Self::low_base() + 2).write(low);
+    }
+  }
+
+  /// Stops the low timer first, so that an overflow can't happen after the
+  /// high timer has stopped counting them.
+  fn stop_controls() {
+    unsafe {
+      VolAddress::<TimerControl>::new(Self::low_base() + 2).write(TimerControl::new());
+      VolAddress::<TimerControl>::new(Self::high_base() + 2).write(TimerControl::new());
+    }
+  }
+
+  /// Starts counting up from 0 at a tick rate.
+  pub fn start(&mut self, rate: TimerTickRate) {
+    Self::stop_controls();
+    unsafe {
+      WOVolAddress::<u16>::new(Self::low_base()).write(0);
+      WOVolAddress::<u16>::new(Self::high_base()).write(0);
+    }
+    Self::start_controls(
+      TimerControl::new().with_tick_rate(rate).with_enabled(true),
+      TimerControl::new().with_cascade(true).with_enabled(true),
+    );
+  }
+
+  /// Stops both timers, keeping the current count.
+  pub fn stop(&mut self) {
+    Self::stop_controls();
+  }
+
+  /// Reads the 32-bit count.
+  ///
+  /// The low timer can overflow between reading the two halves, so this
+  /// reads the high half before and after and tries again if it changed.
+  pub fn read(&self) -> u32 {
+    loop {
+      let high = Self::high_counter();
+      let low = Self::low_counter();
+      if Self::high_counter() == high {
+        return (u32::from(high) << 16) | u32::from(low);
+      }
+    }
+  }
+}
+
+/// Measures CPU cycles with a [CascadeTimer] ticking at `CPU1`.
+///
+/// The count wraps after about 256 seconds, and the elapsed time is correct
+/// across one wrap (but not more).
+#[derive(Debug)]
+pub struct Stopwatch<const N: usize> {
+  timer: CascadeTimer<N>,
+  start: u32,
+}
+
+impl<const N: usize> Stopwatch<N> {
+  /// Restarts the timers and begins measuring.
+  pub fn start(mut timer: CascadeTimer<N>) -> Self {
+    timer.start(TimerTickRate::CPU1);
+    let start = timer.read();
+    Self { timer, start }
+  }
+
+  /// The cycles since the stopwatch was started.
+  pub fn elapsed(&self) -> u32 {
+    self.timer.read().wrapping_sub(self.start)
+  }
+
+  /// Stops the timers and gives them back.
+  pub fn stop(self) -> CascadeTimer<N> {
+    let mut timer = self.timer;
+    timer.stop();
+    timer
+  }
+
+  /// Runs a closure and gives back its result along with how many cycles it
+  /// took.
+  ///
+  /// The stopwatch keeps running, so this can be called any number of times
+  /// (the closure has to take less than about 256 seconds). This includes a
+  /// few cycles of overhead from reading the timers.
+  pub fn measure<R, F: FnOnce() -> R>(&self, f: F) -> (R, u32) {
+    let start = self.timer.read();
+    let result = f();
+    (result, self.timer.read().wrapping_sub(start))
+  }
+}
@@ -0,0 +1,124 @@
+use super::*;
+use typenum::consts::{U1024, U4096};
+
+/// The size of one screenblock in bytes.
+pub const SCREENBLOCK_SIZE: usize = 0x800;
+
+/// The size of one charblock in bytes.
+pub const CHARBLOCK_SIZE: usize = 0x4000;
+
+/// A text mode screenblock, which is 32x32 entries in row-major order.
+///
+/// Text maps bigger than 32x32 use two or four screenblocks in a row, one for
+/// each 32x32 quarter of the map.
+pub type TextScreenblock = VolBlock<TextScreenEntry, U1024>;
+
+/// An affine mode screenblock, seen as pairs of entries.
+///
+/// Each `u16` holds two [AffineScreenEntry] values (see
+/// [AffineScreenEntry::pack_pair]). Affine maps are a single square array of
+/// entries, so the larger sizes just continue on into the following
+/// screenblocks.
+pub type AffineScreenblock = VolBlock<u16, U1024>;
+
+/// A charblock of tile data, seen as `u32` words.
+///
+/// Charblocks 0 through 3 are for backgrounds, and 4 and 5 are for objects.
+pub type Charblock = VolBlock<u32, U4096>;
+
+/// Gets the text screenblock with the index given.
+///
+/// This is the same index as `BackgroundControl::screen_base_block`.
+///
+/// # Panics
+///
+/// If the index is 32 or more.
+pub fn text_screenblock(index: usize) -> TextScreenblock {
+  assert!(index < 32, "screenblock index out of range");
+  unsafe { VolBlock::new(VRAM_BASE_USIZE + index * SCREENBLOCK_SIZE) }
+}
+
+/// Gets the affine screenblock with the index given.
+///
+/// This is the same index as `BackgroundControl::screen_base_block`.
+///
+/// # Panics
+///
+/// If the index is 32 or more.
+pub fn affine_screenblock(index: usize) -> AffineScreenblock {
+  assert!(index < 32, "screenblock index out of range");
+  unsafe { VolBlock::new(VRAM_BASE_USIZE + index * SCREENBLOCK_SIZE) }
+}
+
+/// Gets the charblock with the index given.
+///
+/// For backgrounds this is the same index as
+/// `BackgroundControl::char_base_block`.
+///
+/// # Panics
+///
+/// If the index is 6 or more.
+pub fn charblock(index: usize) -> Charblock {
+  assert!(index < 6, "charblock index out of range");
+  unsafe { VolBlock::new(VRAM_BASE_USIZE + index * CHARBLOCK_SIZE) }
+}
+
+/// The start of the object tile region (charblock 4).
+pub const OBJ_TILE_BASE_USIZE: usize = VRAM_BASE_USIZE + 4 * CHARBLOCK_SIZE;
+
+/// Gets the address of a background tile.
+///
+/// The `char_base_block` is as in `BackgroundControl::char_base_block`, and
+/// the `tile_index` is as in `TextScreenEntry::tile_id` (or the entry itself
+/// for affine backgrounds). In 8bpp mode each index step is 64 bytes instead
+/// of 32.
+///
+/// Tile indexes can go past the end of their charblock and into the next
+/// ones, but background tiles can't be in charblocks 4 or 5, so those tiles
+/// will just be blank.
+pub const fn bg_tile_address(char_base_block: u16, tile_index: u16, is_8bpp: bool) -> usize {
+  let tile_size = if is_8bpp { 64 } else { 32 };
+  VRAM_BASE_USIZE
+    + ((char_base_block & 0b11) as usize) * CHARBLOCK_SIZE
+    + ((tile_index & 0x3FF) as usize) * tile_size
+}
+
+/// Gets a 4bpp background tile (see [bg_tile_address]).
+pub const fn bg_tile_4bpp(char_base_block: u16, tile_index: u16) -> VolAddress<Tile4bpp> {
+  unsafe { VolAddress::new(bg_tile_address(char_base_block, tile_index, false)) }
+}
+
+/// Gets an 8bpp background tile (see [bg_tile_address]).
+pub const fn bg_tile_8bpp(char_base_block: u16, tile_index: u16) -> VolAddress<Tile8bpp> {
+  unsafe { VolAddress::new(bg_tile_address(char_base_block, tile_index, true)) }
+}
+
+/// The first object tile index that can be used in a display mode.
+///
+/// In the bitmap modes (3, 4, and 5) the bitmap uses charblock 4, so only
+/// object tiles 512 and up (charblock 5) can be used. See also
+/// `obj_tile_range`.
+pub const fn first_obj_tile(mode: DisplayMode) -> u16 {
+  obj_tile_range(mode).start
+}
+
+/// Gets the address of an object tile.
+///
+/// The `tile_index` is as in `OBJAttr2::tile_id`, which always counts in 32
+/// byte steps, even for 8bpp objects. Check the index against
+/// [first_obj_tile] for the display mode you're using.
+pub const fn obj_tile_address(tile_index: u16) -> usize {
+  OBJ_TILE_BASE_USIZE + ((tile_index & 0x3FF) as usize) * 32
+}
+
+/// Gets a 4bpp object tile (see [obj_tile_address]).
+pub const fn obj_tile_4bpp(tile_index: u16) -> VolAddress<Tile4bpp> {
+  unsafe { VolAddress::new(obj_tile_address(tile_index)) }
+}
+
+/// Gets an 8bpp object tile (see [obj_tile_address]).
+///
+/// An 8bpp tile covers two tile indexes, so this should be an even index.
+pub const fn obj_tile_8bpp(tile_index: u16) -> VolAddress<Tile8bpp> {
+  unsafe { VolAddress::new(obj_tile_address(tile_index)) }
+}
@@ -0,0 +1,163 @@
+//! Object attribute memory (OAM) helpers.
+//!
+//! Writing to OAM while the display is drawing can make objects tear or
+//! flicker, so the usual approach is to keep a [ShadowOam] in normal RAM,
+//! update it however you like during the frame, and then commit it all at
+//! once during VBlank.
+
+use crate::{
+  affine::AffineMatrix,
+  data::{ObjAttributes, FP_I_7_8},
+};
+
+/// A copy of all of OAM: 128 object entries plus the 32 interleaved affine
+/// parameter sets.
+#[derive(Debug, Clone)]
+#[repr(C, align(4))]
+pub struct ShadowOam {
+  entries: [ObjAttributes; 128],
+}
+impl ShadowOam {
+  /// The number of object entries.
+  pub const OBJECT_COUNT: usize = 128;
+
+  /// The number of affine parameter sets.
+  pub const AFFINE_COUNT: usize = 32;
+
+  /// Makes a buffer with every object hidden and every affine set zeroed.
+  pub const fn new() -> Self {
+    Self { entries: [ObjAttributes::HIDDEN; 128] }
+  }
+
+  /// Gets object entry `n`.
+  ///
+  /// The `padding` field of the value given back is always zero.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 128 or more.
+  pub fn object(&self, n: usize) -> ObjAttributes {
+    ObjAttributes { padding: 0, ..self.entries[n] }
+  }
+
+  /// Sets object entry `n`, leaving the affine parameters alone.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 128 or more.
+  pub fn set_object(&mut self, n: usize, attrs: ObjAttributes) {
+    let entry = &mut self.entries[n];
+    *entry = ObjAttributes { padding: entry.padding, ..attrs };
+  }
+
+  /// Hides object entry `n`.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 128 or more.
+  pub fn hide_object(&mut self, n: usize) {
+    self.set_object(n, ObjAttributes::HIDDEN);
+  }
+
+  /// Hides every object entry.
+  pub fn hide_all(&mut self) {
+    for n in 0..Self::OBJECT_COUNT {
+      self.hide_object(n);
+    }
+  }
+
+  /// Gets affine parameter set `n`.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 32 or more.
+  pub fn affine(&self, n: usize) -> AffineMatrix {
+    let group = &self.entries[n * 4..n * 4 + 4];
+    AffineMatrix::new(
+      FP_I_7_8(group[0].padding),
+      FP_I_7_8(group[1].padding),
+      FP_I_7_8(group[2].padding),
+      FP_I_7_8(group[3].padding),
+    )
+  }
+
+  /// Sets affine parameter set `n`.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 32 or more.
+  pub fn set_affine(&mut self, n: usize, matrix: &AffineMatrix) {
+    let group = &mut self.entries[n * 4..n * 4 + 4];
+    group[0].padding = matrix.pa.0;
+    group[1].padding = matrix.pb.0;
+    group[2].padding = matrix.pc.0;
+    group[3].padding = matrix.pd.0;
+  }
+
+  /// The buffer as `u32` words, in the same layout as OAM.
+  pub fn as_words(&self) -> &[u32; 256] {
+    // Safety: ObjAttributes is 8 bytes with 4 byte alignment and has no
+    // padding bytes, so 128 of them are exactly 256 initialized words.
+    unsafe { &*(self.entries.as_ptr() as *const [u32; 256]) }
+  }
+}
+impl Default for ShadowOam {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::data::{OBJAttr0, OBJAttr1, OBJAttr2};
+
+  fn object(n: u16) -> ObjAttributes {
+    ObjAttributes {
+      attr0: OBJAttr0::new().with_row_coordinate(n),
+      attr1: OBJAttr1::new().with_col_coordinate(n + 1),
+      attr2: OBJAttr2::new().with_tile_id(n + 2),
+      padding: 0,
+    }
+  }
+
+  #[test]
+  fn new_is_hidden() {
+    let oam = ShadowOam::new();
+    for n in 0..ShadowOam::OBJECT_COUNT {
+      assert_eq!(oam.object(n), ObjAttributes::HIDDEN);
+    }
+    for n in 0..ShadowOam::AFFINE_COUNT {
+      assert_eq!(
+        oam.affine(n),
+        AffineMatrix::new(FP_I_7_8(0), FP_I_7_8(0), FP_I_7_8(0), FP_I_7_8(0))
+      );
+    }
+  }
+
+  #[test]
+  fn interleaving() {
+    let mut oam = ShadowOam::new();
+    let matrix = AffineMatrix::new(FP_I_7_8(1), FP_I_7_8(-2), FP_I_7_8(3), FP_I_7_8(-4));
+    oam.set_affine(1, &matrix);
+    // Objects keep the affine parameters that share their entries.
+    for n in 0..8 {
+      oam.set_object(n, ObjAttributes { padding: 99, ..object(n as u16 * 10) });
+    }
+    assert_eq!(oam.affine(1), matrix);
+    assert_eq!(
+      oam.affine(0),
+      AffineMatrix::new(FP_I_7_8(0), FP_I_7_8(0), FP_I_7_8(0), FP_I_7_8(0))
+    );
+    assert_eq!(oam.object(5), object(50));
+    // The affine set is in the last halfword of objects 4 through 7.
+    let words = oam.as_words();
+    assert_eq!(words[8], 40 | 41 << 16);
+    assert_eq!(words[9], 42 | 1 << 16);
+    assert_eq!(words[11], 52 | 0xFFFE << 16);
+    assert_eq!(words[15], 72 | 0xFFFC << 16);
+    oam.hide_all();
+    assert_eq!(oam.object(5), ObjAttributes::HIDDEN);
+    assert_eq!(oam.affine(1), matrix);
+  }
+}
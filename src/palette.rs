@@ -0,0 +1,116 @@
+//! A RAM copy of the palettes, for palette effects.
+//!
+//! Like with OAM, it's best to make your palette changes in RAM and then
+//! commit them all at once during VBlank. Fades also need the original colors
+//! kept around, which the buffer gives you a place for.
+
+use crate::data::Color;
+
+/// A copy of both 256 color palettes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteBuffer {
+  /// Background colors, the same as `PALRAM_BG`.
+  pub bg: [Color; 256],
+  /// Object colors, the same as `PALRAM_OBJ`.
+  pub obj: [Color; 256],
+}
+impl PaletteBuffer {
+  /// Makes a buffer with every color black.
+  pub const fn new() -> Self {
+    Self { bg: [Color::BLACK; 256], obj: [Color::BLACK; 256] }
+  }
+
+  /// Gets background palbank `n` (0 to 15) as a 16 color slice.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 16 or more.
+  pub fn bg_bank_mut(&mut self, n: usize) -> &mut [Color] {
+    &mut self.bg[n * 16..n * 16 + 16]
+  }
+
+  /// Gets object palbank `n` (0 to 15) as a 16 color slice.
+  ///
+  /// # Panics
+  ///
+  /// If `n` is 16 or more.
+  pub fn obj_bank_mut(&mut self, n: usize) -> &mut [Color] {
+    &mut self.obj[n * 16..n * 16 + 16]
+  }
+
+  /// A copy of the buffer with every color moved towards a target color.
+  ///
+  /// The position `t` is x/256 as in `Color::lerp`, so 0 gives the colors
+  /// as-is and 256 gives all `target`.
+  pub fn faded_to(&self, target: Color, t: u16) -> Self {
+    let mut out = self.clone();
+    for color in out.bg.iter_mut().chain(out.obj.iter_mut()) {
+      *color = color.lerp(target, t);
+    }
+    out
+  }
+
+  /// A copy of the buffer faded towards black (see
+  /// [faded_to](PaletteBuffer::faded_to)).
+  pub fn faded_to_black(&self, t: u16) -> Self {
+    self.faded_to(Color::BLACK, t)
+  }
+}
+impl Default for PaletteBuffer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn buffer() -> PaletteBuffer {
+    let mut buffer = PaletteBuffer::new();
+    buffer.bg_bank_mut(1)[2] = Color::rgb(20, 10, 0);
+    buffer.obj_bank_mut(15)[15] = Color::WHITE;
+    buffer
+  }
+
+  #[test]
+  fn banks() {
+    let buffer = buffer();
+    assert_eq!(buffer.bg[18], Color::rgb(20, 10, 0));
+    assert_eq!(buffer.obj[255], Color::WHITE);
+    assert_eq!(buffer.bg.iter().filter(|&&c| c != Color::BLACK).count(), 1);
+  }
+
+  #[test]
+  fn fade_endpoints() {
+    let buffer = buffer();
+    assert_eq!(buffer.faded_to_black(0), buffer);
+    assert_eq!(buffer.faded_to(Color::WHITE, 0), buffer);
+    let black = buffer.faded_to_black(256);
+    assert!(black.bg.iter().chain(black.obj.iter()).all(|&c| c == Color::BLACK));
+    let white = buffer.faded_to(Color::WHITE, 300);
+    assert!(white.bg.iter().chain(white.obj.iter()).all(|&c| c == Color::WHITE));
+  }
+
+  #[test]
+  fn fade_steps() {
+    let buffer = buffer();
+    let half = buffer.faded_to_black(128);
+    assert_eq!(half.bg[18], Color::rgb(10, 5, 0));
+    assert_eq!(half.obj[255], Color::rgb(16, 16, 16));
+    let quarter = buffer.faded_to(Color::WHITE, 64);
+    assert_eq!(quarter.bg[18], Color::rgb(23, 15, 8));
+    assert_eq!(quarter.bg[0], Color::rgb(8, 8, 8));
+    assert_eq!(quarter.obj[255], Color::WHITE);
+    // Fading doesn't change the original.
+    assert_eq!(buffer.bg[18], Color::rgb(20, 10, 0));
+    // Each step gets darker (or stays the same).
+    let mut last = buffer.bg[18].red();
+    for t in (0..=256).step_by(16) {
+      let red = buffer.faded_to_black(t).bg[18].red();
+      assert!(red <= last);
+      last = red;
+    }
+    assert_eq!(last, 0);
+  }
+}
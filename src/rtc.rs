@@ -0,0 +1,437 @@
+//! Driver for the Seiko S-3511 real-time clock.
+//!
+//! This is the RTC used by the carts that have one (the Pokémon games, Boktai,
+//! and so on). It's connected to the cart GPIO port (see the [gpio](crate::gpio)
+//! module), with pin 0 as the serial clock, pin 1 as serial data, and pin 2
+//! as chip select.
+//!
+//! The chip stores everything in BCD, but this driver converts to and from
+//! plain binary values for you. The driver also assumes that the chip is in
+//! 24-hour mode, which [Rtc::init] sets up.
+
+use crate::{
+  data::{GpioControl, GpioData, GpioDirection},
+  gpio::GpioPins,
+};
+use gba_proc_macro::phantom_fields;
+
+#[cfg(any(test, feature = "std"))]
+mod sim;
+#[cfg(any(test, feature = "std"))]
+pub use sim::*;
+
+const CMD_RESET: u8 = 0x60;
+const CMD_STATUS: u8 = 0x62;
+const CMD_DATE_TIME: u8 = 0x64;
+const CMD_TIME: u8 = 0x66;
+const CMD_ALARM: u8 = 0x68;
+const READ: u8 = 1;
+const WRITE: u8 = 0;
+
+newtype! {
+  /// The RTC status register.
+  ///
+  /// * 1: Frequency interrupt enable
+  /// * 3: Per-minute interrupt enable
+  /// * 5: Alarm interrupt enable
+  /// * 6: 24-hour mode (otherwise 12-hour mode)
+  /// * 7: Power failure (read only), set if the chip lost power since the
+  ///   last reset
+  ///
+  /// The interrupts are signaled on the cart IRQ line.
+  RtcStatus, u8
+}
+#[allow(missing_docs)]
+impl RtcStatus {
+  phantom_fields! {
+    self.0: u8,
+    frequency_irq: 1,
+    per_minute_irq: 3,
+    alarm_irq: 5,
+    hour_24: 6,
+    power_failure: 7,
+  }
+}
+
+/// A date and time as stored by the RTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateTime {
+  /// The year within the century, 0 to 99.
+  pub year: u8,
+  /// 1 to 12.
+  pub month: u8,
+  /// 1 to 31.
+  pub day: u8,
+  /// 0 to 6. The chip just counts these up, so which day is 0 is up to you.
+  pub weekday: u8,
+  /// 0 to 23.
+  pub hour: u8,
+  /// 0 to 59.
+  pub minute: u8,
+  /// 0 to 59.
+  pub second: u8,
+}
+impl DateTime {
+  /// If all of the fields are within range.
+  ///
+  /// This doesn't check the day against the length of the month.
+  pub fn is_valid(&self) -> bool {
+    self.year <= 99
+      && (1..=12).contains(&self.month)
+      && (1..=31).contains(&self.day)
+      && self.weekday <= 6
+      && self.time().is_valid()
+  }
+
+  /// Just the time of day part.
+  pub fn time(&self) -> Time {
+    Time { hour: self.hour, minute: self.minute, second: self.second }
+  }
+}
+
+/// A time of day as stored by the RTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Time {
+  /// 0 to 23.
+  pub hour: u8,
+  /// 0 to 59.
+  pub minute: u8,
+  /// 0 to 59.
+  pub second: u8,
+}
+impl Time {
+  /// If all of the fields are within range.
+  pub fn is_valid(&self) -> bool {
+    self.hour <= 23 && self.minute <= 59 && self.second <= 59
+  }
+}
+
+/// The ways that an RTC operation can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtcError {
+  /// The value given to write is out of range.
+  InvalidValue,
+  /// The chip sent back something that isn't a valid BCD value. This usually
+  /// means that there's no RTC on the cart.
+  BadData,
+}
+
+/// Converts a BCD byte to binary, if it's valid BCD.
+fn from_bcd(bcd: u8) -> Result<u8, RtcError> {
+  let (high, low) = (bcd >> 4, bcd & 0xF);
+  if high > 9 || low > 9 {
+    Err(RtcError::BadData)
+  } else {
+    Ok(high * 10 + low)
+  }
+}
+
+/// Converts a binary value (0 to 99) to a BCD byte.
+fn to_bcd(value: u8) -> u8 {
+  ((value / 10) << 4) | (value % 10)
+}
+
+/// The S-3511 real-time clock.
+#[derive(Debug)]
+pub struct Rtc<P: GpioPins> {
+  pins: P,
+}
+impl<P: GpioPins> Rtc<P> {
+  /// Makes an RTC driver using the pins given.
+  ///
+  /// This enables reading the GPIO port, which the driver needs.
+  pub fn new(mut pins: P) -> Self {
+    pins.set_control(GpioControl::new().with_readable(true));
+    Self { pins }
+  }
+
+  /// Gives back the pins.
+  pub fn into_inner(self) -> P {
+    self.pins
+  }
+
+  /// Gets the chip ready to use, giving the status from before any changes.
+  ///
+  /// If the chip lost power or isn't in 24-hour mode then it's reset and put
+  /// into 24-hour mode. A reset sets the date and time to 2000-01-01 00:00:00.
+  pub fn init(&mut self) -> Result<RtcStatus, RtcError> {
+    let status = self.read_status();
+    if status.0 == 0xFF {
+      // The data line floats high when there's nothing there.
+      return Err(RtcError::BadData);
+    }
+    if status.power_failure() || !status.hour_24() {
+      self.reset();
+      self.write_status(RtcStatus::new().with_hour_24(true));
+    }
+    Ok(status)
+  }
+
+  /// Resets the chip, which clears the date, time, and status.
+  pub fn reset(&mut self) {
+    self.begin();
+    self.send_command(CMD_RESET | WRITE);
+    self.end();
+  }
+
+  /// Reads the status register.
+  pub fn read_status(&mut self) -> RtcStatus {
+    let mut buf = [0];
+    self.read_bytes(CMD_STATUS, &mut buf);
+    RtcStatus(buf[0])
+  }
+
+  /// Writes the status register.
+  ///
+  /// The power failure bit can't be written, and is ignored.
+  pub fn write_status(&mut self, status: RtcStatus) {
+    self.write_bytes(CMD_STATUS, &[status.with_power_failure(false).0]);
+  }
+
+  /// Reads the date and time.
+  pub fn read_date_time(&mut self) -> Result<DateTime, RtcError> {
+    let mut buf = [0; 7];
+    self.read_bytes(CMD_DATE_TIME, &mut buf);
+    let date_time = DateTime {
+      year: from_bcd(buf[0])?,
+      month: from_bcd(buf[1])?,
+      day: from_bcd(buf[2])?,
+      weekday: from_bcd(buf[3])?,
+      hour: from_bcd(buf[4] & 0x3F)?,
+      minute: from_bcd(buf[5])?,
+      second: from_bcd(buf[6])?,
+    };
+    if date_time.is_valid() {
+      Ok(date_time)
+    } else {
+      Err(RtcError::BadData)
+    }
+  }
+
+  /// Writes the date and time.
+  pub fn write_date_time(&mut self, date_time: &DateTime) -> Result<(), RtcError> {
+    if !date_time.is_valid() {
+      return Err(RtcError::InvalidValue);
+    }
+    self.write_bytes(
+      CMD_DATE_TIME,
+      &[
+        to_bcd(date_time.year),
+        to_bcd(date_time.month),
+        to_bcd(date_time.day),
+        to_bcd(date_time.weekday),
+        to_bcd(date_time.hour),
+        to_bcd(date_time.minute),
+        to_bcd(date_time.second),
+      ],
+    );
+    Ok(())
+  }
+
+  /// Reads just the time of day.
+  pub fn read_time(&mut self) -> Result<Time, RtcError> {
+    let mut buf = [0; 3];
+    self.read_bytes(CMD_TIME, &mut buf);
+    let time =
+      Time { hour: from_bcd(buf[0] & 0x3F)?, minute: from_bcd(buf[1])?, second: from_bcd(buf[2])? };
+    if time.is_valid() {
+      Ok(time)
+    } else {
+      Err(RtcError::BadData)
+    }
+  }
+
+  /// Writes just the time of day, leaving the date alone.
+  pub fn write_time(&mut self, time: &Time) -> Result<(), RtcError> {
+    if !time.is_valid() {
+      return Err(RtcError::InvalidValue);
+    }
+    self.write_bytes(CMD_TIME, &[to_bcd(time.hour), to_bcd(time.minute), to_bcd(time.second)]);
+    Ok(())
+  }
+
+  /// Sets the alarm time.
+  ///
+  /// When the alarm interrupt is enabled in the status register, the cart IRQ
+  /// fires once the time reaches this hour and minute.
+  pub fn set_alarm(&mut self, hour: u8, minute: u8) -> Result<(), RtcError> {
+    if hour > 23 || minute > 59 {
+      return Err(RtcError::InvalidValue);
+    }
+    // The alarm compares the PM flag too, even in 24-hour mode.
+    let pm = if hour >= 12 { 0x80 } else { 0 };
+    self.write_bytes(CMD_ALARM, &[to_bcd(hour) | pm, to_bcd(minute)]);
+    Ok(())
+  }
+
+  fn set_pins(&mut self, sck: bool, sio: bool, cs: bool) {
+    self.pins.write_data(GpioData::new().with_pin0(sck).with_pin1(sio).with_pin2(cs));
+  }
+
+  /// Starts a transfer by raising CS, with all pins as outputs.
+  fn begin(&mut self) {
+    self.set_pins(true, false, false);
+    self.set_pins(true, false, true);
+    self.pins.set_direction(
+      GpioDirection::new().with_pin0_output(true).with_pin1_output(true).with_pin2_output(true),
+    );
+  }
+
+  /// Ends a transfer by dropping CS.
+  fn end(&mut self) {
+    self.set_pins(true, false, false);
+    self.set_pins(true, false, false);
+  }
+
+  /// Sends one bit, which the chip takes on the rising edge of SCK.
+  fn send_bit(&mut self, bit: bool) {
+    // The extra writes are to hold the clock low for long enough.
+    self.set_pins(false, bit, true);
+    self.set_pins(false, bit, true);
+    self.set_pins(false, bit, true);
+    self.set_pins(true, bit, true);
+  }
+
+  /// Sends the command byte, which goes most significant bit first.
+  fn send_command(&mut self, cmd: u8) {
+    for i in (0..8).rev() {
+      self.send_bit((cmd >> i) & 1 != 0);
+    }
+  }
+
+  /// Sends one parameter byte, which goes least significant bit first.
+  fn send_byte(&mut self, byte: u8) {
+    for i in 0..8 {
+      self.send_bit((byte >> i) & 1 != 0);
+    }
+  }
+
+  /// Receives one parameter byte, which comes least significant bit first.
+  fn receive_byte(&mut self) -> u8 {
+    let mut byte = 0;
+    for i in 0..8 {
+      self.set_pins(false, false, true);
+      self.set_pins(false, false, true);
+      self.set_pins(false, false, true);
+      self.set_pins(false, false, true);
+      self.set_pins(true, false, true);
+      if self.pins.read_data().pin1() {
+        byte |= 1 << i;
+      }
+    }
+    byte
+  }
+
+  fn read_bytes(&mut self, cmd: u8, buf: &mut [u8]) {
+    self.begin();
+    self.send_command(cmd | READ);
+    self.pins.set_direction(GpioDirection::new().with_pin0_output(true).with_pin2_output(true));
+    for byte in buf.iter_mut() {
+      *byte = self.receive_byte();
+    }
+    self.end();
+  }
+
+  fn write_bytes(&mut self, cmd: u8, data: &[u8]) {
+    self.begin();
+    self.send_command(cmd | WRITE);
+    for byte in data.iter() {
+      self.send_byte(*byte);
+    }
+    self.end();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A cart with no RTC, so the data line floats high.
+  struct NoChip;
+  impl GpioPins for NoChip {
+    fn write_data(&mut self, _data: GpioData) {}
+    fn read_data(&mut self) -> GpioData {
+      GpioData::new().with_pin0(true).with_pin1(true).with_pin2(true).with_pin3(true)
+    }
+    fn set_direction(&mut self, _dir: GpioDirection) {}
+    fn set_control(&mut self, _ctrl: GpioControl) {}
+  }
+
+  const DATE_TIME: DateTime =
+    DateTime { year: 21, month: 12, day: 31, weekday: 5, hour: 23, minute: 59, second: 58 };
+
+  #[test]
+  fn bcd() {
+    for value in 0..=99 {
+      assert_eq!(from_bcd(to_bcd(value)), Ok(value));
+    }
+    assert_eq!(to_bcd(59), 0x59);
+    assert_eq!(from_bcd(0x1A), Err(RtcError::BadData));
+    assert_eq!(from_bcd(0xA1), Err(RtcError::BadData));
+  }
+
+  #[test]
+  fn init() {
+    let mut rtc = Rtc::new(SimRtc::new());
+    let status = rtc.init().unwrap();
+    assert!(status.power_failure());
+    assert_eq!(rtc.read_status(), RtcStatus::new().with_hour_24(true));
+    assert_eq!(
+      rtc.read_date_time(),
+      Ok(DateTime { year: 0, month: 1, day: 1, weekday: 0, hour: 0, minute: 0, second: 0 })
+    );
+    // Once set up, another init leaves the chip alone.
+    rtc.write_date_time(&DATE_TIME).unwrap();
+    let status = rtc.init().unwrap();
+    assert_eq!(status, RtcStatus::new().with_hour_24(true));
+    assert_eq!(rtc.read_date_time(), Ok(DATE_TIME));
+    // A chip in 12-hour mode gets reset.
+    let mut sim = rtc.into_inner();
+    sim.set_status(RtcStatus::new());
+    let mut rtc = Rtc::new(sim);
+    assert_eq!(rtc.init(), Ok(RtcStatus::new()));
+    assert_eq!(rtc.into_inner().date_time_bcd(), [0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00]);
+  }
+
+  #[test]
+  fn no_chip() {
+    let mut rtc = Rtc::new(NoChip);
+    assert_eq!(rtc.init(), Err(RtcError::BadData));
+    assert_eq!(rtc.read_date_time(), Err(RtcError::BadData));
+    assert_eq!(rtc.read_time(), Err(RtcError::BadData));
+  }
+
+  #[test]
+  fn date_time() {
+    let mut rtc = Rtc::new(SimRtc::new());
+    rtc.init().unwrap();
+    rtc.write_date_time(&DATE_TIME).unwrap();
+    assert_eq!(rtc.read_date_time(), Ok(DATE_TIME));
+    assert_eq!(rtc.read_time(), Ok(DATE_TIME.time()));
+    rtc.write_time(&Time { hour: 9, minute: 8, second: 7 }).unwrap();
+    let bad = DateTime { month: 13, ..DATE_TIME };
+    assert_eq!(rtc.write_date_time(&bad), Err(RtcError::InvalidValue));
+    assert_eq!(
+      rtc.write_time(&Time { hour: 24, minute: 0, second: 0 }),
+      Err(RtcError::InvalidValue)
+    );
+    let mut sim = rtc.into_inner();
+    assert_eq!(sim.date_time_bcd(), [0x21, 0x12, 0x31, 0x05, 0x09, 0x08, 0x07]);
+    // Valid BCD, but not a valid month.
+    sim.set_date_time_bcd([0x21, 0x13, 0x31, 0x05, 0x09, 0x08, 0x07]);
+    assert_eq!(Rtc::new(sim).read_date_time(), Err(RtcError::BadData));
+  }
+
+  #[test]
+  fn alarm() {
+    let mut rtc = Rtc::new(SimRtc::new());
+    rtc.init().unwrap();
+    rtc.set_alarm(13, 5).unwrap();
+    assert_eq!(rtc.set_alarm(24, 0), Err(RtcError::InvalidValue));
+    assert_eq!(rtc.set_alarm(0, 60), Err(RtcError::InvalidValue));
+    rtc.write_status(RtcStatus::new().with_hour_24(true).with_alarm_irq(true));
+    assert!(rtc.read_status().alarm_irq());
+    let sim = rtc.into_inner();
+    assert_eq!(sim.alarm_bcd(), [0x93, 0x05]);
+  }
+}
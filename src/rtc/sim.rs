@@ -0,0 +1,231 @@
+use super::*;
+
+/// The date and time after a reset: 2000-01-01 00:00:00, weekday 0.
+const RESET_DATE_TIME: [u8; 7] = [0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00];
+
+/// What a [SimRtc] is doing with the bits clocked in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+  /// CS is low, or the command wasn't one the chip knows.
+  Idle,
+  /// Receiving the command byte.
+  Command,
+  /// Sending the register on each clock.
+  Read,
+  /// Receiving parameter bytes.
+  Write,
+}
+
+/// A simulated S-3511 on the cart GPIO port, for testing [Rtc] code off the
+/// GBA.
+///
+/// It follows the serial protocol: commands are clocked in most significant
+/// bit first while CS is high, then parameters go either way least significant
+/// bit first. Writes take effect when CS drops, and only if the right number of
+/// bytes was sent. Time doesn't pass on its own; use
+/// [set_date_time_bcd](SimRtc::set_date_time_bcd) to move it along.
+///
+/// A new chip has lost power, as if the battery was just put in, so the status
+/// has the power failure bit set.
+#[derive(Debug, Clone)]
+pub struct SimRtc {
+  status: u8,
+  date_time: [u8; 7],
+  alarm: [u8; 2],
+  //
+  data: GpioData,
+  direction: GpioDirection,
+  control: GpioControl,
+  sck: bool,
+  cs: bool,
+  sio_out: bool,
+  //
+  phase: Phase,
+  register: u8,
+  bits: usize,
+  buffer: [u8; 7],
+}
+impl Default for SimRtc {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl SimRtc {
+  /// Makes a chip that has just been powered on.
+  pub fn new() -> Self {
+    Self {
+      status: RtcStatus::new().with_power_failure(true).0,
+      date_time: RESET_DATE_TIME,
+      alarm: [0; 2],
+      data: GpioData::new(),
+      direction: GpioDirection::new(),
+      control: GpioControl::new(),
+      sck: false,
+      cs: false,
+      sio_out: false,
+      phase: Phase::Idle,
+      register: 0,
+      bits: 0,
+      buffer: [0; 7],
+    }
+  }
+
+  /// The status register.
+  pub fn status(&self) -> RtcStatus {
+    RtcStatus(self.status)
+  }
+
+  /// Sets the status register, including the power failure bit.
+  pub fn set_status(&mut self, status: RtcStatus) {
+    self.status = status.0;
+  }
+
+  /// The date and time registers, in BCD, year first.
+  pub fn date_time_bcd(&self) -> [u8; 7] {
+    self.date_time
+  }
+
+  /// Sets the date and time registers, in BCD, year first.
+  pub fn set_date_time_bcd(&mut self, date_time: [u8; 7]) {
+    self.date_time = date_time;
+  }
+
+  /// The alarm registers, in BCD: the hour (with bit 7 as the PM flag) and the
+  /// minute.
+  pub fn alarm_bcd(&self) -> [u8; 2] {
+    self.alarm
+  }
+
+  /// The number of parameter bytes for a register.
+  fn register_len(register: u8) -> usize {
+    match register {
+      1 => 1,
+      2 => 7,
+      3 => 3,
+      4 => 2,
+      _ => 0,
+    }
+  }
+
+  /// Loads a register into the buffer, for reading.
+  fn load(&mut self, register: u8) {
+    match register {
+      1 => self.buffer[0] = self.status,
+      2 => self.buffer = self.date_time,
+      3 => self.buffer[..3].copy_from_slice(&self.date_time[4..]),
+      4 => self.buffer[..2].copy_from_slice(&self.alarm),
+      _ => (),
+    }
+    // The hour always has the PM flag in bit 7, even in 24-hour mode.
+    let hour = match register {
+      2 => 4,
+      3 => 0,
+      _ => return,
+    };
+    if self.buffer[hour] >= 0x12 {
+      self.buffer[hour] |= 0x80;
+    }
+  }
+
+  /// Stores a fully written register.
+  fn store(&mut self) {
+    let len = Self::register_len(self.register);
+    if len == 0 || self.bits != len * 8 {
+      return;
+    }
+    match self.register {
+      1 => {
+        let power_failure = RtcStatus(self.status).power_failure();
+        self.status = RtcStatus(self.buffer[0] & 0x6A).with_power_failure(power_failure).0;
+      }
+      2 => self.date_time = self.buffer,
+      3 => self.date_time[4..].copy_from_slice(&self.buffer[..3]),
+      4 => self.alarm.copy_from_slice(&self.buffer[..2]),
+      _ => (),
+    }
+  }
+
+  /// Handles a rising edge of SCK while CS is high.
+  fn clock(&mut self, sio: bool) {
+    match self.phase {
+      Phase::Idle => (),
+      Phase::Command => {
+        self.buffer[0] |= (sio as u8) << (7 - self.bits);
+        self.bits += 1;
+        if self.bits == 8 {
+          let cmd = self.buffer[0];
+          self.register = (cmd >> 1) & 7;
+          self.bits = 0;
+          self.buffer = [0; 7];
+          self.phase = if cmd & 0xF0 != 0x60 {
+            Phase::Idle
+          } else if cmd & READ != 0 {
+            self.load(self.register);
+            Phase::Read
+          } else {
+            if self.register == 0 {
+              self.status = 0;
+              self.date_time = RESET_DATE_TIME;
+              self.alarm = [0; 2];
+            }
+            Phase::Write
+          };
+        }
+      }
+      Phase::Read => {
+        let byte = self.buffer.get(self.bits / 8).copied().unwrap_or(0);
+        self.sio_out = (byte >> (self.bits % 8)) & 1 != 0;
+        self.bits += 1;
+      }
+      Phase::Write => {
+        if let Some(byte) = self.buffer.get_mut(self.bits / 8) {
+          *byte |= (sio as u8) << (self.bits % 8);
+        }
+        self.bits += 1;
+      }
+    }
+  }
+
+  /// Works out the pin levels after a change, and reacts to any edges.
+  fn update(&mut self) {
+    let sck = self.direction.pin0_output() && self.data.pin0();
+    let sio = self.direction.pin1_output() && self.data.pin1();
+    let cs = self.direction.pin2_output() && self.data.pin2();
+    if !cs {
+      if self.cs && self.phase == Phase::Write {
+        self.store();
+      }
+      self.phase = Phase::Idle;
+    } else if !self.cs {
+      self.phase = Phase::Command;
+      self.bits = 0;
+      self.buffer = [0; 7];
+    } else if sck && !self.sck {
+      self.clock(sio);
+    }
+    self.sck = sck;
+    self.cs = cs;
+  }
+}
+impl GpioPins for SimRtc {
+  fn write_data(&mut self, data: GpioData) {
+    self.data = data;
+    self.update();
+  }
+  fn read_data(&mut self) -> GpioData {
+    if !self.control.readable() {
+      GpioData::new()
+    } else if self.direction.pin1_output() {
+      self.data
+    } else {
+      self.data.with_pin1(self.sio_out)
+    }
+  }
+  fn set_direction(&mut self, dir: GpioDirection) {
+    self.direction = dir;
+    self.update();
+  }
+  fn set_control(&mut self, ctrl: GpioControl) {
+    self.control = ctrl;
+  }
+}
@@ -0,0 +1,72 @@
+//! Drivers for cartridge save memory: SRAM, Flash, and EEPROM.
+//!
+//! A cart has at most one kind of save chip, and there's no reliable way to
+//! detect which one from the GBA side, so you have to know what your cart uses
+//! ahead of time (emulators guess based on ID strings in the ROM, such as
+//! `SRAM_V`, `FLASH1M_V`, or `EEPROM_V`).
+//!
+//! The protocol logic here only talks to the chip through the [SaveBus] and
+//! [EepromBus] traits. On the GBA you use the implementations in the `mmio`
+//! module, and elsewhere you can use a simulated chip for testing (with the
+//! `std` feature).
+//!
+//! Note that SRAM and Flash should have the SRAM wait set to 8 cycles in
+//! `WAITCNT`, which is also the default.
+
+mod sram;
+pub use sram::*;
+
+mod flash;
+pub use flash::*;
+
+mod eeprom;
+pub use eeprom::*;
+
+#[cfg(any(test, feature = "std"))]
+mod sim;
+#[cfg(any(test, feature = "std"))]
+pub use sim::*;
+
+/// Byte access to the SRAM / Flash region of the cart (`0xE00_0000`).
+///
+/// This region has an 8-bit data bus, so all access must be one byte at a
+/// time.
+pub trait SaveBus {
+  /// Reads the byte at the offset from the start of the region.
+  fn read(&mut self, offset: usize) -> u8;
+  /// Writes the byte at the offset from the start of the region.
+  fn write(&mut self, offset: usize, value: u8);
+}
+
+/// Serial access to an EEPROM chip.
+///
+/// The EEPROM only transfers one bit at a time, using bit 0 of each `u16`, and
+/// each request must be sent as a single burst (which is why the hardware
+/// version uses DMA3).
+pub trait EepromBus {
+  /// Sends a complete request to the chip.
+  fn send(&mut self, bits: &[u16]);
+  /// Receives a complete response from the chip.
+  fn receive(&mut self, bits: &mut [u16]);
+}
+
+/// The ways that a save operation can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveError {
+  /// The requested range goes past the end of the save memory.
+  OutOfBounds,
+  /// The chip didn't finish an operation in time.
+  Timeout,
+  /// The data read back after writing didn't match.
+  VerifyFailed,
+  /// The Flash chip ID isn't one that this crate knows about.
+  UnknownFlashChip(u16),
+}
+
+/// Checks that `offset..offset+len` fits within `size`.
+fn check_bounds(offset: usize, len: usize, size: usize) -> Result<(), SaveError> {
+  match offset.checked_add(len) {
+    Some(end) if end <= size => Ok(()),
+    _ => Err(SaveError::OutOfBounds),
+  }
+}
@@ -0,0 +1,226 @@
+use super::*;
+
+/// The two sizes of EEPROM.
+///
+/// The size can't be detected, and it changes how many address bits each
+/// request uses, so you have to pick the right one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EepromSize {
+  /// 512 bytes, with 6 bit block addresses.
+  B512,
+  /// 8k, with 14 bit block addresses (only the low 10 bits are used).
+  B8k,
+}
+impl EepromSize {
+  /// The total size in bytes.
+  pub fn size(self) -> usize {
+    match self {
+      EepromSize::B512 => 512,
+      EepromSize::B8k => 0x2000,
+    }
+  }
+
+  /// The number of address bits in each request.
+  pub fn address_bits(self) -> usize {
+    match self {
+      EepromSize::B512 => 6,
+      EepromSize::B8k => 14,
+    }
+  }
+}
+
+/// How many times to poll the chip after a write before giving up.
+const POLL_LIMIT: usize = 0x10_0000;
+
+/// The longest request: 2 command bits, 14 address bits, 64 data bits, and 1
+/// stop bit.
+const MAX_REQUEST: usize = 2 + 14 + 64 + 1;
+
+/// Serial EEPROM (512 bytes or 8k).
+///
+/// EEPROM is accessed in blocks of 8 bytes. Each block is sent as 64 bits,
+/// with the most significant bit of the first byte going first.
+#[derive(Debug)]
+pub struct Eeprom<B: EepromBus> {
+  bus: B,
+  size: EepromSize,
+}
+impl<B: EepromBus> Eeprom<B> {
+  /// The size of one block in bytes.
+  pub const BLOCK_SIZE: usize = 8;
+
+  /// Makes an EEPROM driver for the size given.
+  pub fn new(bus: B, size: EepromSize) -> Self {
+    Self { bus, size }
+  }
+
+  /// Gives back the bus.
+  pub fn into_inner(self) -> B {
+    self.bus
+  }
+
+  /// The size of the chip this driver is using.
+  pub fn size(&self) -> EepromSize {
+    self.size
+  }
+
+  /// Fills in the command and address bits of a request, giving the number of
+  /// bits used.
+  fn request_header(&self, bits: &mut [u16], cmd: [u16; 2], block: usize) -> usize {
+    bits[0] = cmd[0];
+    bits[1] = cmd[1];
+    let addr_bits = self.size.address_bits();
+    for i in 0..addr_bits {
+      bits[2 + i] = ((block >> (addr_bits - 1 - i)) & 1) as u16;
+    }
+    2 + addr_bits
+  }
+
+  /// Reads one 8 byte block.
+  pub fn read_block(&mut self, block: usize, buf: &mut [u8; 8]) -> Result<(), SaveError> {
+    let start = block.checked_mul(Self::BLOCK_SIZE).ok_or(SaveError::OutOfBounds)?;
+    check_bounds(start, Self::BLOCK_SIZE, self.size.size())?;
+    let mut request = [0_u16; MAX_REQUEST];
+    let len = self.request_header(&mut request, [1, 1], block);
+    request[len] = 0;
+    self.bus.send(&request[..len + 1]);
+    // The response is 4 junk bits followed by the 64 data bits.
+    let mut response = [0_u16; 68];
+    self.bus.receive(&mut response);
+    for (i, byte) in buf.iter_mut().enumerate() {
+      *byte =
+        response[4 + i * 8..4 + i * 8 + 8].iter().fold(0, |acc, bit| (acc << 1) | (*bit as u8 & 1));
+    }
+    Ok(())
+  }
+
+  /// Writes one 8 byte block, then waits for the chip to finish.
+  pub fn write_block(&mut self, block: usize, data: &[u8; 8]) -> Result<(), SaveError> {
+    let start = block.checked_mul(Self::BLOCK_SIZE).ok_or(SaveError::OutOfBounds)?;
+    check_bounds(start, Self::BLOCK_SIZE, self.size.size())?;
+    let mut request = [0_u16; MAX_REQUEST];
+    let mut len = self.request_header(&mut request, [1, 0], block);
+    for byte in data.iter() {
+      for i in (0..8).rev() {
+        request[len] = u16::from((byte >> i) & 1);
+        len += 1;
+      }
+    }
+    request[len] = 0;
+    self.bus.send(&request[..len + 1]);
+    let mut status = [0_u16; 1];
+    for _ in 0..POLL_LIMIT {
+      self.bus.receive(&mut status);
+      if status[0] & 1 == 1 {
+        return Ok(());
+      }
+    }
+    Err(SaveError::Timeout)
+  }
+
+  /// Reads bytes starting at any offset.
+  pub fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), SaveError> {
+    check_bounds(offset, buf.len(), self.size.size())?;
+    let mut block_buf = [0_u8; 8];
+    let mut done = 0;
+    while done < buf.len() {
+      let pos = offset + done;
+      self.read_block(pos / Self::BLOCK_SIZE, &mut block_buf)?;
+      let start = pos % Self::BLOCK_SIZE;
+      let count = (Self::BLOCK_SIZE - start).min(buf.len() - done);
+      buf[done..done + count].copy_from_slice(&block_buf[start..start + count]);
+      done += count;
+    }
+    Ok(())
+  }
+
+  /// Writes bytes starting at any offset.
+  ///
+  /// Blocks that are only partly covered are read first so that the rest of
+  /// their data is kept.
+  pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    check_bounds(offset, data.len(), self.size.size())?;
+    let mut block_buf = [0_u8; 8];
+    let mut done = 0;
+    while done < data.len() {
+      let pos = offset + done;
+      let block = pos / Self::BLOCK_SIZE;
+      let start = pos % Self::BLOCK_SIZE;
+      let count = (Self::BLOCK_SIZE - start).min(data.len() - done);
+      if count < Self::BLOCK_SIZE {
+        self.read_block(block, &mut block_buf)?;
+      }
+      block_buf[start..start + count].copy_from_slice(&data[done..done + count]);
+      self.write_block(block, &block_buf)?;
+      done += count;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::vec::Vec;
+
+  /// Passes everything along to a [SimEeprom], keeping the requests sent.
+  struct Recorder {
+    sim: SimEeprom,
+    sent: Vec<Vec<u16>>,
+  }
+  impl EepromBus for Recorder {
+    fn send(&mut self, bits: &[u16]) {
+      self.sent.push(bits.to_vec());
+      self.sim.send(bits);
+    }
+    fn receive(&mut self, bits: &mut [u16]) {
+      self.sim.receive(bits);
+    }
+  }
+
+  #[test]
+  fn bit_protocol() {
+    let mut sim = SimEeprom::new(EepromSize::B512);
+    sim.data_mut()[40..48].copy_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0x01]);
+    let mut eeprom = Eeprom::new(Recorder { sim, sent: Vec::new() }, EepromSize::B512);
+    let mut buf = [0; 8];
+    eeprom.read_block(5, &mut buf).unwrap();
+    assert_eq!(buf, [0x80, 0, 0, 0, 0, 0, 0, 0x01]);
+    eeprom.write_block(63, &[0xC0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+    let recorder = eeprom.into_inner();
+    assert_eq!(recorder.sent[0], [1, 1, 0, 0, 0, 1, 0, 1, 0]);
+    let write = &recorder.sent[1];
+    assert_eq!(write.len(), 2 + 6 + 64 + 1);
+    assert_eq!(write[..10], [1, 0, 1, 1, 1, 1, 1, 1, 1, 1]);
+    assert!(write[10..].iter().all(|&bit| bit == 0));
+    assert_eq!(recorder.sim.data()[504..], [0xC0, 0, 0, 0, 0, 0, 0, 0]);
+  }
+
+  #[test]
+  fn read_write() {
+    for &size in [EepromSize::B512, EepromSize::B8k].iter() {
+      let mut eeprom = Eeprom::new(SimEeprom::new(size), size);
+      let end = size.size();
+      eeprom.write(5, b"hello world").unwrap();
+      eeprom.write(end - 3, b"end").unwrap();
+      let mut buf = [0; 13];
+      eeprom.read(4, &mut buf).unwrap();
+      assert_eq!(&buf, b"\xFFhello world\xFF");
+      let sim = eeprom.into_inner();
+      assert_eq!(&sim.data()[end - 4..], b"\xFFend");
+    }
+  }
+
+  #[test]
+  fn bounds() {
+    let mut eeprom = Eeprom::new(SimEeprom::new(EepromSize::B512), EepromSize::B512);
+    let mut buf = [0; 8];
+    assert_eq!(eeprom.read_block(64, &mut buf), Err(SaveError::OutOfBounds));
+    assert_eq!(eeprom.write_block(64, &buf), Err(SaveError::OutOfBounds));
+    // Big enough that the byte offset would overflow.
+    let huge = usize::max_value() / 4;
+    assert_eq!(eeprom.read_block(huge, &mut buf), Err(SaveError::OutOfBounds));
+    assert_eq!(eeprom.write_block(huge, &buf), Err(SaveError::OutOfBounds));
+    assert_eq!(eeprom.write(510, b"abc"), Err(SaveError::OutOfBounds));
+  }
+}
@@ -0,0 +1,335 @@
+use super::*;
+
+newtype_enum! {
+  /// The Flash chips used in GBA carts.
+  ///
+  /// The value of each tag is the chip ID, with the device code in the high
+  /// byte and the manufacturer code in the low byte.
+  FlashChip = u16,
+  /// SST, 64k
+  Sst64k = 0xD4BF,
+  /// Macronix, 64k
+  Macronix64k = 0x1CC2,
+  /// Panasonic, 64k
+  Panasonic64k = 0x1B32,
+  /// Atmel, 64k. This chip writes 128 byte pages instead of single bytes.
+  Atmel64k = 0x3D1F,
+  /// Sanyo, 128k
+  Sanyo128k = 0x1362,
+  /// Macronix, 128k
+  Macronix128k = 0x09C2,
+}
+impl FlashChip {
+  /// Gets the chip for an ID value, if it's a known chip.
+  pub fn from_id(id: u16) -> Option<Self> {
+    match id {
+      0xD4BF => Some(FlashChip::Sst64k),
+      0x1CC2 => Some(FlashChip::Macronix64k),
+      0x1B32 => Some(FlashChip::Panasonic64k),
+      0x3D1F => Some(FlashChip::Atmel64k),
+      0x1362 => Some(FlashChip::Sanyo128k),
+      0x09C2 => Some(FlashChip::Macronix128k),
+      _ => None,
+    }
+  }
+
+  /// The total size of the chip in bytes.
+  pub fn size(self) -> usize {
+    match self {
+      FlashChip::Sanyo128k | FlashChip::Macronix128k => 0x2_0000,
+      _ => 0x1_0000,
+    }
+  }
+
+  /// The size of the chip's erase unit in bytes.
+  pub fn sector_size(self) -> usize {
+    match self {
+      FlashChip::Atmel64k => 128,
+      _ => 0x1000,
+    }
+  }
+}
+
+/// How many times to poll the chip before giving up on an operation.
+///
+/// Erasing a sector can take up to 20ms on some chips, which is a few tens of
+/// thousands of polls, so this leaves a lot of room.
+const POLL_LIMIT: usize = 0x10_0000;
+
+/// Flash memory (64k or 128k).
+///
+/// The chip only sees 64k at a time, so the 128k chips use a bank switch
+/// command to pick which half is visible. This driver handles the bank
+/// switching for you, so all offsets are for the whole chip.
+///
+/// Flash can only change bits from 1 to 0 when writing, so a sector has to be
+/// erased (back to all `0xFF`) before it can be written again.
+#[derive(Debug)]
+pub struct Flash<B: SaveBus> {
+  bus: B,
+  chip: FlashChip,
+  bank: usize,
+}
+impl<B: SaveBus> Flash<B> {
+  /// Reads the chip ID and makes a driver for that chip.
+  pub fn new(mut bus: B) -> Result<Self, SaveError> {
+    let id = read_chip_id(&mut bus);
+    let chip = FlashChip::from_id(id).ok_or(SaveError::UnknownFlashChip(id))?;
+    let mut flash = Self { bus, chip, bank: 0 };
+    if chip.size() > 0x1_0000 {
+      flash.switch_bank(0);
+    }
+    Ok(flash)
+  }
+
+  /// Makes a driver for a known chip, without checking the ID.
+  pub fn with_chip(bus: B, chip: FlashChip) -> Self {
+    // The bank isn't known, so make sure the first access switches it.
+    Self { bus, chip, bank: usize::max_value() }
+  }
+
+  /// Gives back the bus.
+  pub fn into_inner(self) -> B {
+    self.bus
+  }
+
+  /// The chip this driver is using.
+  pub fn chip(&self) -> FlashChip {
+    self.chip
+  }
+
+  /// Sends the command prefix, then the command byte.
+  fn command(&mut self, cmd: u8) {
+    self.bus.write(0x5555, 0xAA);
+    self.bus.write(0x2AAA, 0x55);
+    self.bus.write(0x5555, cmd);
+  }
+
+  /// Makes sure the bank holding the offset is visible, and gives the offset
+  /// within that bank.
+  fn select(&mut self, offset: usize) -> usize {
+    let bank = offset >> 16;
+    if self.chip.size() > 0x1_0000 && bank != self.bank {
+      self.switch_bank(bank);
+    }
+    offset & 0xFFFF
+  }
+
+  fn switch_bank(&mut self, bank: usize) {
+    self.command(0xB0);
+    self.bus.write(0x0000, bank as u8);
+    self.bank = bank;
+  }
+
+  /// Polls until the byte at the address reads back as the expected value.
+  fn wait_for(&mut self, addr: usize, expected: u8) -> Result<(), SaveError> {
+    for _ in 0..POLL_LIMIT {
+      if self.bus.read(addr) == expected {
+        return Ok(());
+      }
+    }
+    Err(SaveError::Timeout)
+  }
+
+  /// Reads bytes starting at the offset.
+  pub fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), SaveError> {
+    check_bounds(offset, buf.len(), self.chip.size())?;
+    for (i, byte) in buf.iter_mut().enumerate() {
+      let addr = self.select(offset + i);
+      *byte = self.bus.read(addr);
+    }
+    Ok(())
+  }
+
+  /// Erases one sector (see `FlashChip::sector_size`) back to all `0xFF`.
+  ///
+  /// For the Atmel chip this does nothing, because its pages are erased
+  /// automatically when they're written.
+  pub fn erase_sector(&mut self, sector: usize) -> Result<(), SaveError> {
+    let sector_size = self.chip.sector_size();
+    let start = sector.checked_mul(sector_size).ok_or(SaveError::OutOfBounds)?;
+    check_bounds(start, sector_size, self.chip.size())?;
+    if self.chip == FlashChip::Atmel64k {
+      return Ok(());
+    }
+    let addr = self.select(start);
+    self.command(0x80);
+    self.bus.write(0x5555, 0xAA);
+    self.bus.write(0x2AAA, 0x55);
+    self.bus.write(addr, 0x30);
+    self.wait_for(addr, 0xFF)
+  }
+
+  /// Erases the entire chip back to all `0xFF`.
+  pub fn erase_all(&mut self) -> Result<(), SaveError> {
+    self.command(0x80);
+    self.command(0x10);
+    self.wait_for(0x0000, 0xFF)
+  }
+
+  /// Writes bytes starting at the offset, which must already be erased.
+  ///
+  /// For the Atmel chip the writes happen a whole page at a time, so the rest
+  /// of any partially written page is read and written back as well.
+  pub fn program(&mut self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    check_bounds(offset, data.len(), self.chip.size())?;
+    if self.chip == FlashChip::Atmel64k {
+      return self.program_atmel(offset, data);
+    }
+    for (i, byte) in data.iter().enumerate() {
+      let addr = self.select(offset + i);
+      self.command(0xA0);
+      self.bus.write(addr, *byte);
+      self.wait_for(addr, *byte).map_err(|_| SaveError::VerifyFailed)?;
+    }
+    Ok(())
+  }
+
+  fn program_atmel(&mut self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    const PAGE: usize = 128;
+    let mut page_buf = [0_u8; PAGE];
+    let mut page_start = offset - offset % PAGE;
+    while page_start < offset + data.len() {
+      self.read(page_start, &mut page_buf)?;
+      for (i, slot) in page_buf.iter_mut().enumerate() {
+        let addr = page_start + i;
+        if addr >= offset && addr < offset + data.len() {
+          *slot = data[addr - offset];
+        }
+      }
+      self.command(0xA0);
+      for (i, byte) in page_buf.iter().enumerate() {
+        self.bus.write(page_start + i, *byte);
+      }
+      let last = page_start + PAGE - 1;
+      self.wait_for(last, page_buf[PAGE - 1]).map_err(|_| SaveError::VerifyFailed)?;
+      page_start += PAGE;
+    }
+    Ok(())
+  }
+
+  /// Erases each sector touched by the range and then writes the data.
+  ///
+  /// Any other data in those sectors is lost, so this is best used with
+  /// sector aligned data.
+  pub fn erase_and_program(&mut self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    check_bounds(offset, data.len(), self.chip.size())?;
+    let sector_size = self.chip.sector_size();
+    if !data.is_empty() {
+      for sector in (offset / sector_size)..=((offset + data.len() - 1) / sector_size) {
+        self.erase_sector(sector)?;
+      }
+    }
+    self.program(offset, data)
+  }
+}
+
+/// Reads the Flash chip ID, with the device code in the high byte and the
+/// manufacturer code in the low byte.
+pub fn read_chip_id<B: SaveBus>(bus: &mut B) -> u16 {
+  bus.write(0x5555, 0xAA);
+  bus.write(0x2AAA, 0x55);
+  bus.write(0x5555, 0x90);
+  let manufacturer = bus.read(0x0000);
+  let device = bus.read(0x0001);
+  bus.write(0x5555, 0xAA);
+  bus.write(0x2AAA, 0x55);
+  bus.write(0x5555, 0xF0);
+  u16::from(manufacturer) | (u16::from(device) << 8)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CHIPS: [FlashChip; 6] = [
+    FlashChip::Sst64k,
+    FlashChip::Macronix64k,
+    FlashChip::Panasonic64k,
+    FlashChip::Atmel64k,
+    FlashChip::Sanyo128k,
+    FlashChip::Macronix128k,
+  ];
+
+  #[test]
+  fn chip_id() {
+    for &chip in CHIPS.iter() {
+      let mut sim = SimFlash::new(chip);
+      assert_eq!(read_chip_id(&mut sim), chip as u16);
+      // The ID mode is exited again.
+      assert_eq!(sim.read(0x0000), 0xFF);
+      assert_eq!(Flash::new(sim).unwrap().chip(), chip);
+    }
+    let mut sram = SimSram::new();
+    sram.data_mut()[0] = 0x34;
+    sram.data_mut()[1] = 0x12;
+    assert_eq!(Flash::new(sram).unwrap_err(), SaveError::UnknownFlashChip(0x1234));
+  }
+
+  #[test]
+  fn program_and_erase() {
+    let mut flash = Flash::new(SimFlash::new(FlashChip::Macronix64k)).unwrap();
+    flash.program(0x0FFE, b"abcd").unwrap();
+    let mut buf = [0; 4];
+    flash.read(0x0FFE, &mut buf).unwrap();
+    assert_eq!(&buf, b"abcd");
+    // Programming can't set bits that are already clear.
+    assert_eq!(flash.program(0x0FFE, b"\xFF"), Err(SaveError::VerifyFailed));
+    // Erasing the second sector leaves the first alone.
+    flash.erase_sector(1).unwrap();
+    flash.read(0x0FFE, &mut buf).unwrap();
+    assert_eq!(&buf, b"ab\xFF\xFF");
+    flash.erase_and_program(0x0FFF, b"xy").unwrap();
+    flash.read(0x0FFE, &mut buf).unwrap();
+    assert_eq!(&buf, b"\xFFxy\xFF");
+    flash.erase_all().unwrap();
+    assert!(flash.into_inner().data().iter().all(|&byte| byte == 0xFF));
+  }
+
+  #[test]
+  fn bank_switch() {
+    let mut flash = Flash::new(SimFlash::new(FlashChip::Sanyo128k)).unwrap();
+    flash.program(0xFFFF, b"ab").unwrap();
+    let mut buf = [0; 2];
+    flash.read(0xFFFF, &mut buf).unwrap();
+    assert_eq!(&buf, b"ab");
+    flash.erase_sector(0x10).unwrap();
+    flash.read(0xFFFF, &mut buf).unwrap();
+    assert_eq!(&buf, b"a\xFF");
+    let sim = flash.into_inner();
+    assert_eq!(sim.bank(), 1);
+    assert_eq!(sim.data()[0xFFFF], b'a');
+    assert_eq!(sim.data()[0x0000], 0xFF);
+    // Without reading the ID, the first access has to switch banks.
+    let mut sim = SimFlash::new(FlashChip::Macronix128k);
+    sim.data_mut()[0x1_0000] = 0;
+    let mut flash = Flash::new(sim).unwrap();
+    flash.read(0x1_0000, &mut buf[..1]).unwrap();
+    let mut flash = Flash::with_chip(flash.into_inner(), FlashChip::Macronix128k);
+    flash.read(0x0000, &mut buf[1..]).unwrap();
+    assert_eq!(buf, [0x00, 0xFF]);
+  }
+
+  #[test]
+  fn atmel_pages() {
+    let mut sim = SimFlash::new(FlashChip::Atmel64k);
+    sim.data_mut()[127] = 1;
+    sim.data_mut()[128] = 2;
+    let mut flash = Flash::new(sim).unwrap();
+    flash.program(126, b"xyz").unwrap();
+    // Atmel pages are rewritten whole, so the old bits don't matter.
+    flash.program(126, b"XY").unwrap();
+    flash.erase_sector(0).unwrap();
+    let sim = flash.into_inner();
+    assert_eq!(&sim.data()[124..130], b"\xFF\xFFXYz\xFF");
+  }
+
+  #[test]
+  fn bounds() {
+    let mut flash = Flash::new(SimFlash::new(FlashChip::Sst64k)).unwrap();
+    assert_eq!(flash.program(0xFFFF, b"ab"), Err(SaveError::OutOfBounds));
+    assert_eq!(flash.erase_sector(0x10), Err(SaveError::OutOfBounds));
+    assert_eq!(flash.erase_sector(usize::max_value()), Err(SaveError::OutOfBounds));
+    assert_eq!(flash.read(0x1_0000, &mut [0]), Err(SaveError::OutOfBounds));
+  }
+}
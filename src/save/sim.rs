@@ -0,0 +1,266 @@
+use super::*;
+use std::{vec, vec::Vec};
+
+/// A simulated SRAM chip, for testing [Sram] code off the GBA.
+#[derive(Debug, Clone)]
+pub struct SimSram {
+  data: Vec<u8>,
+}
+impl Default for SimSram {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl SimSram {
+  /// Makes a chip with every byte `0xFF`.
+  pub fn new() -> Self {
+    Self { data: vec![0xFF; 0x8000] }
+  }
+
+  /// The chip's contents.
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// The chip's contents, for setting up a test.
+  pub fn data_mut(&mut self) -> &mut [u8] {
+    &mut self.data
+  }
+}
+impl SaveBus for SimSram {
+  fn read(&mut self, offset: usize) -> u8 {
+    self.data[offset & 0x7FFF]
+  }
+  fn write(&mut self, offset: usize, value: u8) {
+    self.data[offset & 0x7FFF] = value;
+  }
+}
+
+/// Where a [SimFlash] is in the command prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlashPrefix {
+  None,
+  /// `0xAA` was written to `0x5555`.
+  First,
+  /// Then `0x55` was written to `0x2AAA`.
+  Second,
+}
+
+/// What a [SimFlash] does with the next plain write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlashPending {
+  None,
+  /// Program one byte.
+  Byte,
+  /// Program this many more bytes of an Atmel page.
+  Page(usize),
+  /// Switch to the bank written to `0x0000`.
+  Bank,
+}
+
+/// A simulated Flash chip, for testing [Flash] code off the GBA.
+///
+/// It follows the command sequences of the real chips: reading the ID, erasing
+/// a sector or the whole chip, programming (which can only clear bits), and
+/// switching banks on the 128k chips. Atmel chips program a 128 byte page
+/// after each program command, erasing the page first. Everything finishes
+/// instantly.
+#[derive(Debug, Clone)]
+pub struct SimFlash {
+  chip: FlashChip,
+  data: Vec<u8>,
+  bank: usize,
+  prefix: FlashPrefix,
+  pending: FlashPending,
+  id_mode: bool,
+  erase_armed: bool,
+}
+impl SimFlash {
+  /// Makes a chip with every byte `0xFF`.
+  pub fn new(chip: FlashChip) -> Self {
+    Self {
+      chip,
+      data: vec![0xFF; chip.size()],
+      bank: 0,
+      prefix: FlashPrefix::None,
+      pending: FlashPending::None,
+      id_mode: false,
+      erase_armed: false,
+    }
+  }
+
+  /// The chip being simulated.
+  pub fn chip(&self) -> FlashChip {
+    self.chip
+  }
+
+  /// The visible bank.
+  pub fn bank(&self) -> usize {
+    self.bank
+  }
+
+  /// The chip's contents.
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// The chip's contents, for setting up a test.
+  pub fn data_mut(&mut self) -> &mut [u8] {
+    &mut self.data
+  }
+
+  /// The index into `data` for an offset in the visible bank.
+  fn index(&self, offset: usize) -> usize {
+    (self.bank << 16 | (offset & 0xFFFF)) % self.data.len()
+  }
+
+  fn command(&mut self, cmd: u8) {
+    let erase_armed = core::mem::replace(&mut self.erase_armed, false);
+    match cmd {
+      0x90 => self.id_mode = true,
+      0xF0 => self.id_mode = false,
+      0x80 => self.erase_armed = true,
+      0x10 if erase_armed => self.data.iter_mut().for_each(|byte| *byte = 0xFF),
+      0xA0 if self.chip == FlashChip::Atmel64k => self.pending = FlashPending::Page(128),
+      0xA0 => self.pending = FlashPending::Byte,
+      0xB0 if self.chip.size() > 0x1_0000 => self.pending = FlashPending::Bank,
+      _ => (),
+    }
+  }
+}
+impl SaveBus for SimFlash {
+  fn read(&mut self, offset: usize) -> u8 {
+    match offset {
+      0x0000 if self.id_mode => self.chip as u16 as u8,
+      0x0001 if self.id_mode => (self.chip as u16 >> 8) as u8,
+      _ => self.data[self.index(offset)],
+    }
+  }
+  fn write(&mut self, offset: usize, value: u8) {
+    let index = self.index(offset);
+    match self.pending {
+      FlashPending::Byte => {
+        self.data[index] &= value;
+        self.pending = FlashPending::None;
+        return;
+      }
+      FlashPending::Page(left) => {
+        if left == 128 {
+          let start = index - index % 128;
+          self.data[start..start + 128].iter_mut().for_each(|byte| *byte = 0xFF);
+        }
+        self.data[index] &= value;
+        self.pending = if left > 1 { FlashPending::Page(left - 1) } else { FlashPending::None };
+        return;
+      }
+      FlashPending::Bank if offset == 0x0000 => {
+        self.bank = usize::from(value & 1);
+        self.pending = FlashPending::None;
+        return;
+      }
+      _ => (),
+    }
+    self.prefix = match (self.prefix, offset, value) {
+      (FlashPrefix::None, 0x5555, 0xAA) => FlashPrefix::First,
+      (FlashPrefix::First, 0x2AAA, 0x55) => FlashPrefix::Second,
+      (FlashPrefix::Second, 0x5555, cmd) => {
+        self.command(cmd);
+        FlashPrefix::None
+      }
+      (FlashPrefix::Second, _, 0x30) if self.erase_armed => {
+        self.erase_armed = false;
+        let start = index - index % 0x1000;
+        self.data[start..start + 0x1000].iter_mut().for_each(|byte| *byte = 0xFF);
+        FlashPrefix::None
+      }
+      _ => FlashPrefix::None,
+    };
+  }
+}
+
+/// A simulated EEPROM chip, for testing [Eeprom] code off the GBA.
+///
+/// After each write the chip reports busy for a couple of polls.
+///
+/// # Panics
+///
+/// Sending a request that isn't a well formed read or write for this size of
+/// chip panics, since the hardware would just ignore it.
+#[derive(Debug, Clone)]
+pub struct SimEeprom {
+  size: EepromSize,
+  data: Vec<u8>,
+  /// The block to send back on the next receive.
+  read_block: Option<usize>,
+  /// How many more polls report busy.
+  busy: usize,
+}
+impl SimEeprom {
+  /// The number of polls that report busy after each write.
+  pub const BUSY_POLLS: usize = 2;
+
+  /// Makes a chip with every byte `0xFF`.
+  pub fn new(size: EepromSize) -> Self {
+    Self { size, data: vec![0xFF; size.size()], read_block: None, busy: 0 }
+  }
+
+  /// The chip's contents.
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// The chip's contents, for setting up a test.
+  pub fn data_mut(&mut self) -> &mut [u8] {
+    &mut self.data
+  }
+
+  /// Reads the block address at the start of `bits`, ignoring the bits that
+  /// the chip doesn't use.
+  fn block(&self, bits: &[u16]) -> usize {
+    let address = bits.iter().fold(0, |acc, bit| (acc << 1) | usize::from(bit & 1));
+    address % (self.size.size() / 8)
+  }
+}
+impl EepromBus for SimEeprom {
+  fn send(&mut self, bits: &[u16]) {
+    let addr_bits = self.size.address_bits();
+    assert!(bits.len() >= 2 + addr_bits + 1, "EEPROM request too short");
+    assert_eq!(bits[bits.len() - 1] & 1, 0, "EEPROM request missing its stop bit");
+    let block = self.block(&bits[2..2 + addr_bits]);
+    match (bits[0] & 1, bits[1] & 1) {
+      (1, 1) => {
+        assert_eq!(bits.len(), 2 + addr_bits + 1, "EEPROM read request has extra bits");
+        self.read_block = Some(block);
+      }
+      (1, 0) => {
+        assert_eq!(bits.len(), 2 + addr_bits + 64 + 1, "EEPROM write request wrong length");
+        for (i, byte) in self.data[block * 8..block * 8 + 8].iter_mut().enumerate() {
+          let start = 2 + addr_bits + i * 8;
+          *byte = bits[start..start + 8].iter().fold(0, |acc, bit| (acc << 1) | (*bit as u8 & 1));
+        }
+        self.busy = Self::BUSY_POLLS;
+      }
+      _ => panic!("unknown EEPROM command"),
+    }
+  }
+
+  fn receive(&mut self, bits: &mut [u16]) {
+    if let Some(block) = self.read_block.take() {
+      // 4 junk bits, then the data.
+      for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = match i.checked_sub(4) {
+          Some(n) if n < 64 => u16::from(self.data[block * 8 + n / 8] >> (7 - n % 8) & 1),
+          _ => 0,
+        };
+      }
+    } else {
+      let ready = if self.busy > 0 {
+        self.busy -= 1;
+        0
+      } else {
+        1
+      };
+      bits.iter_mut().for_each(|bit| *bit = ready);
+    }
+  }
+}
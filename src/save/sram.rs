@@ -0,0 +1,59 @@
+use super::*;
+
+/// Battery backed SRAM (32k).
+///
+/// SRAM is plain memory, so there's nothing to do besides respecting the 8-bit
+/// bus.
+#[derive(Debug)]
+pub struct Sram<B: SaveBus> {
+  bus: B,
+}
+impl<B: SaveBus> Sram<B> {
+  /// The size of SRAM in bytes.
+  pub const SIZE: usize = 0x8000;
+
+  /// Makes an SRAM driver using the bus given.
+  pub fn new(bus: B) -> Self {
+    Self { bus }
+  }
+
+  /// Gives back the bus.
+  pub fn into_inner(self) -> B {
+    self.bus
+  }
+
+  /// Reads bytes starting at the offset.
+  pub fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), SaveError> {
+    check_bounds(offset, buf.len(), Self::SIZE)?;
+    for (i, byte) in buf.iter_mut().enumerate() {
+      *byte = self.bus.read(offset + i);
+    }
+    Ok(())
+  }
+
+  /// Writes bytes starting at the offset.
+  pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    check_bounds(offset, data.len(), Self::SIZE)?;
+    for (i, byte) in data.iter().enumerate() {
+      self.bus.write(offset + i, *byte);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn read_write() {
+    let mut sram = Sram::new(SimSram::new());
+    sram.write(0x7FFE, b"hi").unwrap();
+    let mut buf = [0; 4];
+    sram.read(0x7FFC, &mut buf).unwrap();
+    assert_eq!(buf, [0xFF, 0xFF, b'h', b'i']);
+    assert_eq!(sram.write(0x7FFF, b"hi"), Err(SaveError::OutOfBounds));
+    assert_eq!(sram.read(usize::max_value(), &mut buf), Err(SaveError::OutOfBounds));
+    assert_eq!(&sram.into_inner().data()[0x7FFE..], b"hi");
+  }
+}
@@ -0,0 +1,555 @@
+//! A simulated GBA memory map for testing register code on any machine.
+//!
+//! [SimBus] is a [RegisterBus] that behaves like the hardware in the ways that
+//! matter for testing interrupt, timer, and DMA code:
+//!
+//! * `IF` is write-1-to-clear.
+//! * Read only bits keep their value when written (the `DisplayStatus` flags,
+//!   the `SoundStatusMaster` channel bits, `VCOUNT`, `KEYINPUT`).
+//! * Write only registers read back as the open bus value instead of what was
+//!   written.
+//! * Timers count as the simulation is stepped, reload on overflow, cascade,
+//!   and raise their interrupt flags.
+//! * Immediate DMA transfers copy within the simulated memory as soon as
+//!   they're enabled. Other start timings are stored but never run.
+//!
+//! There's no CPU, so nothing _takes_ an interrupt. Check
+//! [irq_requested](SimBus::irq_requested) and call your handler yourself.
+//! There's also no LCD, but [set_vcount](SimBus::set_vcount) moves the
+//! scanline along with its flags and interrupts.
+//!
+//! Memory quirks such as VRAM mirroring and the 8-bit write rules of VRAM,
+//! palette RAM, and OAM aren't simulated.
+
+use crate::{
+  bus::{RegisterBus, Width},
+  data::{Interrupt, Keys},
+};
+use std::{vec, vec::Vec};
+
+/// `DISPSTAT`
+const DISPSTAT: usize = 0x004;
+/// `VCOUNT`
+const VCOUNT: usize = 0x006;
+/// `SOUND_STATUS_ENABLE`
+const SOUNDCNT_X: usize = 0x084;
+/// The first DMA register.
+const DMA_BASE: usize = 0x0B0;
+/// The first timer register.
+const TIMER_BASE: usize = 0x100;
+/// `KEYINPUT`
+const KEYINPUT: usize = 0x130;
+/// `KEYCNT`
+const KEYCNT: usize = 0x132;
+/// `IF`
+const IF: usize = 0x202;
+/// `IE`
+const IE: usize = 0x200;
+/// `IME`
+const IME: usize = 0x208;
+/// The size of the IO block.
+const IO_SIZE: usize = 0x400;
+
+/// How a halfword of IO is accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IoKind {
+  /// Normal memory, except for the read only bits.
+  ReadWrite { read_only: u16 },
+  /// Writes are ignored.
+  ReadOnly,
+  /// Reads give the open bus value.
+  WriteOnly,
+  /// Writing a 1 clears that bit.
+  Acknowledge,
+  /// The timer counter (read) and reload (write).
+  TimerCounter(usize),
+  /// The timer control.
+  TimerControl(usize),
+  /// The DMA control.
+  DmaControl(usize),
+}
+
+fn io_kind(offset: usize) -> IoKind {
+  match offset {
+    DISPSTAT => IoKind::ReadWrite { read_only: 0b111 },
+    VCOUNT | KEYINPUT => IoKind::ReadOnly,
+    SOUNDCNT_X => IoKind::ReadWrite { read_only: 0b1111 },
+    IF => IoKind::Acknowledge,
+    // scroll, affine, window position, mosaic, and BLDY
+    0x010..=0x047 | 0x04C..=0x04F | 0x054..=0x055 => IoKind::WriteOnly,
+    // sound FIFOs
+    0x0A0..=0x0A7 => IoKind::WriteOnly,
+    0x0B0..=0x0DF => {
+      let n = (offset - DMA_BASE) / 12;
+      if (offset - DMA_BASE) % 12 == 10 {
+        IoKind::DmaControl(n)
+      } else {
+        IoKind::WriteOnly
+      }
+    }
+    0x100..=0x10F => {
+      let n = (offset - TIMER_BASE) / 4;
+      if offset % 4 == 0 {
+        IoKind::TimerCounter(n)
+      } else {
+        IoKind::TimerControl(n)
+      }
+    }
+    _ => IoKind::ReadWrite { read_only: 0 },
+  }
+}
+
+/// The running state of one timer.
+#[derive(Debug, Clone, Copy, Default)]
+struct TimerState {
+  counter: u16,
+  /// CPU cycles counted toward the next tick.
+  prescale: u32,
+}
+
+/// A simulated GBA memory map. See the module docs.
+#[derive(Debug, Clone)]
+pub struct SimBus {
+  ewram: Vec<u8>,
+  iwram: Vec<u8>,
+  palram: Vec<u8>,
+  vram: Vec<u8>,
+  oam: Vec<u8>,
+  rom: Vec<u8>,
+  /// The last value written to each IO halfword (with read only bits set by
+  /// the simulation).
+  io: Vec<u16>,
+  timers: [TimerState; 4],
+  open_bus: u16,
+}
+impl Default for SimBus {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl SimBus {
+  /// Makes a simulation with all memory 0, no ROM, and no keys pressed.
+  pub fn new() -> Self {
+    let mut io = vec![0; IO_SIZE / 2];
+    io[KEYINPUT / 2] = 0x03FF;
+    Self {
+      ewram: vec![0; 0x4_0000],
+      iwram: vec![0; 0x8000],
+      palram: vec![0; 0x400],
+      vram: vec![0; 0x1_8000],
+      oam: vec![0; 0x400],
+      rom: Vec::new(),
+      io,
+      timers: [TimerState::default(); 4],
+      open_bus: 0,
+    }
+  }
+
+  /// Sets the ROM contents, which are read only from then on.
+  pub fn load_rom(&mut self, rom: &[u8]) {
+    self.rom = rom.to_vec();
+  }
+
+  /// Sets the value that unreadable addresses give (0 by default).
+  ///
+  /// On the hardware this is whatever was last on the bus, usually an
+  /// instruction that was just fetched.
+  pub fn set_open_bus(&mut self, value: u16) {
+    self.open_bus = value;
+  }
+
+  /// Sets an interrupt's bit in `IF`, the same as the hardware would.
+  pub fn raise(&mut self, irq: Interrupt) {
+    self.io[IF / 2] |= irq.mask();
+  }
+
+  /// If the CPU would take an interrupt now: `IME` is on and some interrupt
+  /// is both enabled in `IE` and flagged in `IF`.
+  pub fn irq_requested(&self) -> bool {
+    self.io[IME / 2] & 1 != 0 && self.io[IE / 2] & self.io[IF / 2] != 0
+  }
+
+  /// Sets the keys that are pressed, raising the keypad interrupt if `KEYCNT`
+  /// asks for it.
+  pub fn set_keys(&mut self, keys: Keys) {
+    self.io[KEYINPUT / 2] = !keys.0 & 0x03FF;
+    let keycnt = self.io[KEYCNT / 2];
+    let wanted = keycnt & 0x03FF;
+    if keycnt & (1 << 14) != 0 && wanted != 0 {
+      let and_mode = keycnt & (1 << 15) != 0;
+      let hit = if and_mode { keys.0 & wanted == wanted } else { keys.0 & wanted != 0 };
+      if hit {
+        self.raise(Interrupt::Keypad);
+      }
+    }
+  }
+
+  /// Moves the LCD to a scanline (0 through 227).
+  ///
+  /// This updates `VCOUNT` and the `DISPSTAT` VBlank and VCount flags, and
+  /// raises the VBlank interrupt (when moving to line 160) and the VCount
+  /// interrupt (when moving to the matching line) if `DISPSTAT` enables them.
+  pub fn set_vcount(&mut self, line: u16) {
+    let old = self.io[VCOUNT / 2];
+    self.io[VCOUNT / 2] = line;
+    let dispstat = self.io[DISPSTAT / 2];
+    let vblank = (160..227).contains(&line);
+    let vcount_match = line == dispstat >> 8;
+    self.io[DISPSTAT / 2] = (dispstat & !0b101) | (vblank as u16) | ((vcount_match as u16) << 2);
+    if line != old {
+      if line == 160 && dispstat & (1 << 3) != 0 {
+        self.raise(Interrupt::VBlank);
+      }
+      if vcount_match && dispstat & (1 << 5) != 0 {
+        self.raise(Interrupt::VCounter);
+      }
+    }
+  }
+
+  /// Runs the timers for some number of CPU cycles.
+  pub fn step(&mut self, cycles: u32) {
+    for n in 0..4 {
+      let ctrl = self.io[(TIMER_BASE + n * 4 + 2) / 2];
+      let cascade = n > 0 && ctrl & (1 << 2) != 0;
+      if ctrl & (1 << 7) == 0 || cascade {
+        continue;
+      }
+      let divider = match ctrl & 0b11 {
+        0 => 1,
+        1 => 64,
+        2 => 256,
+        _ => 1024,
+      };
+      // In u64, since a big step plus the leftover cycles can pass u32::MAX.
+      let total = u64::from(self.timers[n].prescale) + u64::from(cycles);
+      self.timers[n].prescale = (total % divider) as u32;
+      self.tick_timer(n, total / divider);
+    }
+  }
+
+  /// Advances a timer, handling overflows.
+  fn tick_timer(&mut self, n: usize, mut ticks: u64) {
+    while ticks > 0 {
+      let until_overflow = 0x1_0000 - u64::from(self.timers[n].counter);
+      if ticks < until_overflow {
+        self.timers[n].counter += ticks as u16;
+        return;
+      }
+      ticks -= until_overflow;
+      self.timers[n].counter = self.io[(TIMER_BASE + n * 4) / 2];
+      self.timer_overflow(n);
+    }
+  }
+
+  fn timer_overflow(&mut self, n: usize) {
+    let ctrl = self.io[(TIMER_BASE + n * 4 + 2) / 2];
+    if ctrl & (1 << 6) != 0 {
+      self.raise(Interrupt::ALL[Interrupt::Timer0 as usize + n]);
+    }
+    if n < 3 {
+      let next = self.io[(TIMER_BASE + (n + 1) * 4 + 2) / 2];
+      if next & (1 << 7) != 0 && next & (1 << 2) != 0 {
+        self.tick_timer(n + 1, 1);
+      }
+    }
+  }
+
+  fn io_read(&self, offset: usize) -> u16 {
+    match io_kind(offset) {
+      IoKind::WriteOnly => self.open_bus,
+      IoKind::TimerCounter(n) => self.timers[n].counter,
+      _ => self.io[offset / 2],
+    }
+  }
+
+  /// Writes the bits of `value` that are set in `mask`.
+  fn io_write(&mut self, offset: usize, value: u16, mask: u16) {
+    let old = self.io[offset / 2];
+    let merged = (old & !mask) | (value & mask);
+    match io_kind(offset) {
+      IoKind::ReadOnly => (),
+      IoKind::Acknowledge => self.io[offset / 2] &= !(value & mask),
+      IoKind::ReadWrite { read_only } => {
+        self.io[offset / 2] = (merged & !read_only) | (old & read_only);
+      }
+      IoKind::WriteOnly | IoKind::TimerCounter(_) => self.io[offset / 2] = merged,
+      IoKind::TimerControl(n) => {
+        self.io[offset / 2] = merged;
+        if old & (1 << 7) == 0 && merged & (1 << 7) != 0 {
+          self.timers[n] = TimerState { counter: self.io[(TIMER_BASE + n * 4) / 2], prescale: 0 };
+        }
+      }
+      IoKind::DmaControl(n) => {
+        self.io[offset / 2] = merged;
+        if old & (1 << 15) == 0 && merged & (1 << 15) != 0 && merged & (0b11 << 12) == 0 {
+          self.run_dma(n);
+        }
+      }
+    }
+  }
+
+  /// Runs an immediate transfer on channel `n`.
+  fn run_dma(&mut self, n: usize) {
+    let base = (DMA_BASE + n * 12) / 2;
+    let ctrl = self.io[base + 5];
+    let mut source = usize::from(self.io[base]) | usize::from(self.io[base + 1]) << 16;
+    let mut dest = usize::from(self.io[base + 2]) | usize::from(self.io[base + 3]) << 16;
+    source &= if n == 0 { 0x07FF_FFFF } else { 0x0FFF_FFFF };
+    dest &= if n == 3 { 0x0FFF_FFFF } else { 0x07FF_FFFF };
+    // DMA0-2 only have a 14-bit count, and 0 means the largest count.
+    let count_mask = if n == 3 { 0xFFFF } else { 0x3FFF };
+    let count = match usize::from(self.io[base + 4]) & count_mask {
+      0 => count_mask + 1,
+      count => count,
+    };
+    let width = if ctrl & (1 << 10) != 0 { Width::U32 } else { Width::U16 };
+    let step = |control: u16, address: usize| match control {
+      1 => address.wrapping_sub(width.bytes()),
+      2 => address,
+      _ => address.wrapping_add(width.bytes()),
+    };
+    let source_control = (ctrl >> 7) & 0b11;
+    let dest_control = (ctrl >> 5) & 0b11;
+    for _ in 0..count {
+      let value = self.bus_read(source, width);
+      self.bus_write(dest, width, value);
+      source = step(source_control, source);
+      dest = step(dest_control, dest);
+    }
+    self.io[base + 5] &= !(1 << 15);
+    if ctrl & (1 << 14) != 0 {
+      self.raise(Interrupt::ALL[Interrupt::Dma0 as usize + n]);
+    }
+  }
+
+  /// The backing memory and index for an address outside of IO, if any.
+  fn memory(&self, address: usize) -> Option<(&[u8], usize)> {
+    let offset = address & 0x00FF_FFFF;
+    match address >> 24 {
+      2 => Some((&self.ewram, offset & 0x3_FFFF)),
+      3 => Some((&self.iwram, offset & 0x7FFF)),
+      5 => Some((&self.palram, offset & 0x3FF)),
+      6 if offset < 0x1_8000 => Some((&self.vram, offset)),
+      7 => Some((&self.oam, offset & 0x3FF)),
+      8 | 9 if address - 0x800_0000 < self.rom.len() => Some((&self.rom, address - 0x800_0000)),
+      _ => None,
+    }
+  }
+
+  fn memory_mut(&mut self, address: usize) -> Option<(&mut [u8], usize)> {
+    let offset = address & 0x00FF_FFFF;
+    match address >> 24 {
+      2 => Some((&mut self.ewram, offset & 0x3_FFFF)),
+      3 => Some((&mut self.iwram, offset & 0x7FFF)),
+      5 => Some((&mut self.palram, offset & 0x3FF)),
+      6 if offset < 0x1_8000 => Some((&mut self.vram, offset)),
+      7 => Some((&mut self.oam, offset & 0x3FF)),
+      _ => None,
+    }
+  }
+
+  fn bus_read(&self, address: usize, width: Width) -> u32 {
+    let address = address & !(width.bytes() - 1);
+    if address >> 24 == 4 {
+      let offset = address & 0x00FF_FFFF;
+      if offset >= IO_SIZE {
+        return u32::from(self.open_bus);
+      }
+      let low = u32::from(self.io_read(offset & !1));
+      return match width {
+        Width::U8 => (low >> ((offset & 1) * 8)) & 0xFF,
+        Width::U16 => low,
+        Width::U32 => low | u32::from(self.io_read(offset + 2)) << 16,
+      };
+    }
+    match self.memory(address) {
+      Some((bytes, index)) => (0..width.bytes())
+        .fold(0, |acc, i| acc | u32::from(bytes.get(index + i).copied().unwrap_or(0)) << (i * 8)),
+      None => width.mask(u32::from(self.open_bus) | u32::from(self.open_bus) << 16),
+    }
+  }
+
+  fn bus_write(&mut self, address: usize, width: Width, value: u32) {
+    let address = address & !(width.bytes() - 1);
+    if address >> 24 == 4 {
+      let offset = address & 0x00FF_FFFF;
+      if offset >= IO_SIZE {
+        return;
+      }
+      match width {
+        Width::U8 => {
+          let shift = (offset & 1) * 8;
+          self.io_write(offset & !1, (value as u16) << shift, 0xFF << shift);
+        }
+        Width::U16 => self.io_write(offset, value as u16, 0xFFFF),
+        Width::U32 => {
+          self.io_write(offset, value as u16, 0xFFFF);
+          self.io_write(offset + 2, (value >> 16) as u16, 0xFFFF);
+        }
+      }
+      return;
+    }
+    if let Some((bytes, index)) = self.memory_mut(address) {
+      for i in 0..width.bytes() {
+        bytes[index + i] = (value >> (i * 8)) as u8;
+      }
+    }
+  }
+}
+impl RegisterBus for SimBus {
+  fn read(&mut self, address: usize, width: Width) -> u32 {
+    self.bus_read(address, width)
+  }
+
+  fn write(&mut self, address: usize, width: Width, value: u32) {
+    self.bus_write(address, width, value)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TM0CNT_L: usize = 0x400_0100;
+  const TM0CNT_H: usize = 0x400_0102;
+  const TM1CNT_L: usize = 0x400_0104;
+  const TM1CNT_H: usize = 0x400_0106;
+
+  /// Sets up and enables DMA channel `n`.
+  fn start_dma(bus: &mut SimBus, n: usize, source: u32, dest: u32, count: u16, ctrl: u16) {
+    let base = 0x400_0000 + DMA_BASE + n * 12;
+    bus.write_u32(base, source);
+    bus.write_u32(base + 4, dest);
+    bus.write_u16(base + 8, count);
+    bus.write_u16(base + 10, ctrl | 0x8000);
+  }
+
+  #[test]
+  fn acknowledge_clears_bits() {
+    let mut bus = SimBus::new();
+    bus.raise(Interrupt::VBlank);
+    bus.raise(Interrupt::Timer0);
+    bus.write_u16(0x400_0200, Interrupt::Timer0.mask());
+    bus.write_u16(0x400_0208, 1);
+    assert!(bus.irq_requested());
+    bus.write_u16(0x400_0202, Interrupt::VBlank.mask());
+    assert_eq!(bus.read_u16(0x400_0202), Interrupt::Timer0.mask());
+    // Zero bits, and bytes that don't cover the flag, leave it alone.
+    bus.write_u16(0x400_0202, 0);
+    bus.write_u8(0x400_0203, 0xFF);
+    assert_eq!(bus.read_u16(0x400_0202), Interrupt::Timer0.mask());
+    bus.write_u8(0x400_0202, 0xFF);
+    assert_eq!(bus.read_u16(0x400_0202), 0);
+    assert!(!bus.irq_requested());
+  }
+
+  #[test]
+  fn read_only_bits() {
+    let mut bus = SimBus::new();
+    bus.set_vcount(160);
+    assert_eq!(bus.read_u16(0x400_0004), 0b001);
+    bus.write_u16(0x400_0004, 0xFFFE);
+    assert_eq!(bus.read_u16(0x400_0004), 0xFFF9);
+    bus.write_u16(0x400_0084, 0x008F);
+    assert_eq!(bus.read_u16(0x400_0084), 0x0080);
+    bus.write_u16(0x400_0006, 5);
+    assert_eq!(bus.read_u16(0x400_0006), 160);
+  }
+
+  #[test]
+  fn write_only_registers() {
+    let mut bus = SimBus::new();
+    bus.set_open_bus(0x1234);
+    bus.write_u16(0x400_0010, 5);
+    assert_eq!(bus.read_u16(0x400_0010), 0x1234);
+    bus.write_u32(0x400_00B0, 0x0200_0000);
+    assert_eq!(bus.read_u32(0x400_00B0), 0x1234_1234);
+    assert_eq!(bus.read_u16(0x400_0400), 0x1234);
+  }
+
+  #[test]
+  fn timers() {
+    let mut bus = SimBus::new();
+    bus.write_u16(TM0CNT_L, 0xFFF0);
+    bus.write_u16(TM1CNT_H, 0x0084);
+    // Prescale 64, with the interrupt.
+    bus.write_u16(TM0CNT_H, 0x00C1);
+    bus.step(64 * 15 + 63);
+    assert_eq!(bus.read_u16(TM0CNT_L), 0xFFFF);
+    assert_eq!(bus.read_u16(0x400_0202), 0);
+    bus.step(1);
+    assert_eq!(bus.read_u16(TM0CNT_L), 0xFFF0);
+    assert_eq!(bus.read_u16(0x400_0202), Interrupt::Timer0.mask());
+    assert_eq!(bus.read_u16(TM1CNT_L), 1);
+    // A cascade timer doesn't count cycles.
+    bus.step(64 * 15);
+    assert_eq!(bus.read_u16(TM0CNT_L), 0xFFFF);
+    assert_eq!(bus.read_u16(TM1CNT_L), 1);
+    bus.step(64 * 18);
+    assert_eq!(bus.read_u16(TM0CNT_L), 0xFFF1);
+    assert_eq!(bus.read_u16(TM1CNT_L), 3);
+  }
+
+  #[test]
+  fn timer_big_steps() {
+    let mut bus = SimBus::new();
+    // Prescale 1024.
+    bus.write_u16(TM0CNT_H, 0x0083);
+    bus.step(1023);
+    bus.step(u32::max_value());
+    assert_eq!(bus.read_u16(TM0CNT_L), 0);
+    bus.step(2);
+    assert_eq!(bus.read_u16(TM0CNT_L), 1);
+  }
+
+  #[test]
+  fn dma_copy() {
+    let mut bus = SimBus::new();
+    for i in 0..4 {
+      bus.write_u16(0x200_0000 + i * 2, i as u16 + 1);
+    }
+    // 16-bit copy with the interrupt.
+    start_dma(&mut bus, 3, 0x200_0000, 0x300_0000, 4, 1 << 14);
+    assert_eq!(bus.read_u32(0x300_0000), 0x0002_0001);
+    assert_eq!(bus.read_u32(0x300_0004), 0x0004_0003);
+    assert_eq!(bus.read_u16(0x400_00DE) & 0x8000, 0);
+    assert_eq!(bus.read_u16(0x400_0202), Interrupt::Dma3.mask());
+    // Decrementing destination.
+    start_dma(&mut bus, 3, 0x200_0000, 0x300_0106, 4, 0b01 << 5);
+    assert_eq!(bus.read_u32(0x300_0100), 0x0003_0004);
+    assert_eq!(bus.read_u32(0x300_0104), 0x0001_0002);
+    // Decrementing 32-bit source.
+    start_dma(&mut bus, 3, 0x200_0004, 0x300_0200, 2, 0b01 << 7 | 1 << 10);
+    assert_eq!(bus.read_u32(0x300_0200), 0x0004_0003);
+    assert_eq!(bus.read_u32(0x300_0204), 0x0002_0001);
+    // Fixed source fills.
+    start_dma(&mut bus, 3, 0x200_0000, 0x300_0300, 3, 0b10 << 7);
+    assert_eq!(bus.read_u32(0x300_0300), 0x0001_0001);
+    assert_eq!(bus.read_u32(0x300_0304), 0x0000_0001);
+    // Fixed destination keeps only the last value.
+    start_dma(&mut bus, 3, 0x200_0000, 0x300_0400, 4, 0b10 << 5);
+    assert_eq!(bus.read_u32(0x300_0400), 4);
+    // Other timings wait.
+    start_dma(&mut bus, 3, 0x200_0000, 0x300_0500, 4, 0b01 << 12);
+    assert_eq!(bus.read_u32(0x300_0500), 0);
+    assert_ne!(bus.read_u16(0x400_00DE) & 0x8000, 0);
+  }
+
+  #[test]
+  fn dma_count() {
+    let mut bus = SimBus::new();
+    bus.write_u16(0x300_0000, 0xABCD);
+    // 0 is the largest count, 0x4000 for DMA0.
+    start_dma(&mut bus, 0, 0x300_0000, 0x200_1000, 0, 0b10 << 7);
+    assert_eq!(bus.read_u16(0x200_1000 + 0x3FFF * 2), 0xABCD);
+    assert_eq!(bus.read_u16(0x200_1000 + 0x4000 * 2), 0);
+    // And 0x1_0000 for DMA3.
+    start_dma(&mut bus, 3, 0x300_0000, 0x200_1000, 0, 0b10 << 7);
+    assert_eq!(bus.read_u16(0x200_1000 + 0xFFFF * 2), 0xABCD);
+    assert_eq!(bus.read_u16(0x200_1000 + 0x1_0000 * 2), 0);
+    // DMA0-2 ignore the top 2 bits of the count.
+    start_dma(&mut bus, 1, 0x300_0000, 0x200_0000, 0x4002, 0b10 << 7);
+    assert_eq!(bus.read_u32(0x200_0000), 0xABCD_ABCD);
+    assert_eq!(bus.read_u16(0x200_0004), 0);
+  }
+}
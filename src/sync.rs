@@ -0,0 +1,329 @@
+//! Sharing data between the main program and interrupt handlers.
+//!
+//! The GBA's CPU has no atomic read-modify-write instructions, so the only way
+//! to keep an interrupt handler from seeing half of an update is to turn
+//! interrupts off for the duration. The `free` function in the `mmio` module
+//! does that, and hands out a [CriticalSection] token while interrupts are
+//! off. [IrqCell] and [IrqMutex] can only be accessed with that token.
+//!
+//! The [SpscRing] is the exception: a single producer and a single consumer
+//! can use it without a critical section, because each index is an atomic
+//! that only one side ever writes, so it only needs atomic loads and stores
+//! (which the GBA does have).
+
+use core::{
+  cell::{Cell, UnsafeCell},
+  marker::PhantomData,
+  mem::MaybeUninit,
+  ptr,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Proof that interrupts are disabled.
+///
+/// You normally only see this as a reference passed to the closure given to
+/// `free`, so it can't outlive the critical section.
+#[derive(Debug)]
+pub struct CriticalSection {
+  _priv: PhantomData<*const ()>,
+}
+impl CriticalSection {
+  /// Makes the token.
+  ///
+  /// # Safety
+  ///
+  /// Interrupts must be disabled for as long as the token exists, and nothing
+  /// else can be running at the same time (which is always true on the GBA).
+  pub const unsafe fn new() -> Self {
+    Self { _priv: PhantomData }
+  }
+}
+
+/// A value that can be shared with interrupt handlers.
+///
+/// The value is only ever copied or swapped in and out, never borrowed.
+pub struct IrqCell<T> {
+  value: UnsafeCell<T>,
+}
+impl<T> IrqCell<T> {
+  /// Makes a new cell.
+  pub const fn new(value: T) -> Self {
+    Self { value: UnsafeCell::new(value) }
+  }
+
+  /// Replaces the value, giving back the old one.
+  pub fn replace(&self, _cs: &CriticalSection, value: T) -> T {
+    unsafe { ptr::replace(self.value.get(), value) }
+  }
+
+  /// Sets the value.
+  pub fn set(&self, cs: &CriticalSection, value: T) {
+    self.replace(cs, value);
+  }
+
+  /// Gets a unique reference, which needs no critical section.
+  pub fn get_mut(&mut self) -> &mut T {
+    self.value.get_mut()
+  }
+
+  /// Unwraps the value.
+  pub fn into_inner(self) -> T {
+    self.value.into_inner()
+  }
+}
+impl<T: Copy> IrqCell<T> {
+  /// Gets the value.
+  pub fn get(&self, _cs: &CriticalSection) -> T {
+    unsafe { *self.value.get() }
+  }
+}
+unsafe impl<T: Send> Sync for IrqCell<T> {}
+impl<T> core::fmt::Debug for IrqCell<T> {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("IrqCell").finish()
+  }
+}
+
+/// A value of any type that can be shared with interrupt handlers.
+///
+/// Locking it needs a [CriticalSection]. Locking it again from inside the
+/// closure given to [lock](IrqMutex::lock) would give out two `&mut` to the
+/// same value, so that panics.
+pub struct IrqMutex<T> {
+  value: UnsafeCell<T>,
+  locked: Cell<bool>,
+}
+impl<T> IrqMutex<T> {
+  /// Makes a new mutex.
+  pub const fn new(value: T) -> Self {
+    Self { value: UnsafeCell::new(value), locked: Cell::new(false) }
+  }
+
+  /// Runs the closure on the value, or gives `None` if the mutex is already
+  /// locked further up the call stack.
+  pub fn try_lock<R>(&self, _cs: &CriticalSection, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    if self.locked.replace(true) {
+      return None;
+    }
+    let out = f(unsafe { &mut *self.value.get() });
+    self.locked.set(false);
+    Some(out)
+  }
+
+  /// Runs the closure on the value.
+  ///
+  /// # Panics
+  ///
+  /// If the mutex is already locked further up the call stack.
+  pub fn lock<R>(&self, cs: &CriticalSection, f: impl FnOnce(&mut T) -> R) -> R {
+    match self.try_lock(cs, f) {
+      Some(out) => out,
+      None => panic!("IrqMutex locked twice"),
+    }
+  }
+
+  /// Gets a unique reference, which needs no critical section.
+  pub fn get_mut(&mut self) -> &mut T {
+    self.value.get_mut()
+  }
+
+  /// Unwraps the value.
+  pub fn into_inner(self) -> T {
+    self.value.into_inner()
+  }
+}
+unsafe impl<T: Send> Sync for IrqMutex<T> {}
+impl<T> core::fmt::Debug for IrqMutex<T> {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("IrqMutex").field("locked", &self.locked.get()).finish()
+  }
+}
+
+/// A fixed size queue with one producer and one consumer, such as an
+/// interrupt handler sending messages to the main loop.
+///
+/// Use [split](SpscRing::split) to get the two ends. It holds up to `N - 1`
+/// items, since one slot is always kept empty to tell "full" from "empty". That
+/// means `N` can't be 0, which is a compile error:
+///
+/// ```compile_fail
+/// static mut RING: gba_hal::sync::SpscRing<u8, 0> = gba_hal::sync::SpscRing::new();
+/// ```
+pub struct SpscRing<T, const N: usize> {
+  buffer: UnsafeCell<MaybeUninit<[T; N]>>,
+  /// The next slot to read, only written by the consumer.
+  head: AtomicUsize,
+  /// The next slot to write, only written by the producer.
+  tail: AtomicUsize,
+}
+impl<T, const N: usize> SpscRing<T, N> {
+  /// Indexes past the end of a 1 element array if `N` is 0, which fails to
+  /// compile wherever `new` is used.
+  const N_IS_NOT_ZERO: () = [()][(N == 0) as usize];
+
+  /// Makes an empty ring.
+  pub const fn new() -> Self {
+    #[allow(clippy::let_unit_value)]
+    let _ = Self::N_IS_NOT_ZERO;
+    Self {
+      buffer: UnsafeCell::new(MaybeUninit::uninit()),
+      head: AtomicUsize::new(0),
+      tail: AtomicUsize::new(0),
+    }
+  }
+
+  /// The most items the ring can hold.
+  pub const fn capacity(&self) -> usize {
+    N - 1
+  }
+
+  /// The number of items in the ring.
+  pub fn len(&self) -> usize {
+    let head = self.head.load(Ordering::Acquire);
+    let tail = self.tail.load(Ordering::Acquire);
+    if tail >= head {
+      tail - head
+    } else {
+      N - head + tail
+    }
+  }
+
+  /// If the ring is empty.
+  pub fn is_empty(&self) -> bool {
+    self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+  }
+
+  /// Splits the ring into its two ends.
+  ///
+  /// For a ring in a `static mut`, do this once during setup and then keep
+  /// the ends (for example in an [IrqCell]) where each side can get to them.
+  pub fn split(&mut self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
+    let ring: &Self = self;
+    (Producer { ring }, Consumer { ring })
+  }
+
+  fn slot(&self, index: usize) -> *mut T {
+    unsafe { (self.buffer.get() as *mut T).add(index) }
+  }
+}
+impl<T, const N: usize> Default for SpscRing<T, N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl<T, const N: usize> Drop for SpscRing<T, N> {
+  fn drop(&mut self) {
+    let (_, mut consumer) = self.split();
+    while consumer.dequeue().is_some() {}
+  }
+}
+unsafe impl<T: Send, const N: usize> Sync for SpscRing<T, N> {}
+impl<T, const N: usize> core::fmt::Debug for SpscRing<T, N> {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("SpscRing")
+      .field("len", &self.len())
+      .field("capacity", &self.capacity())
+      .finish()
+  }
+}
+
+/// The writing end of a [SpscRing].
+#[derive(Debug)]
+pub struct Producer<'a, T, const N: usize> {
+  ring: &'a SpscRing<T, N>,
+}
+impl<'a, T, const N: usize> Producer<'a, T, N> {
+  /// Adds an item, or gives it back if the ring is full.
+  pub fn enqueue(&mut self, item: T) -> Result<(), T> {
+    let tail = self.ring.tail.load(Ordering::Relaxed);
+    let next = (tail + 1) % N;
+    if next == self.ring.head.load(Ordering::Acquire) {
+      return Err(item);
+    }
+    unsafe { self.ring.slot(tail).write(item) };
+    self.ring.tail.store(next, Ordering::Release);
+    Ok(())
+  }
+
+  /// If the ring is full.
+  pub fn is_full(&self) -> bool {
+    (self.ring.tail.load(Ordering::Relaxed) + 1) % N == self.ring.head.load(Ordering::Acquire)
+  }
+}
+
+/// The reading end of a [SpscRing].
+#[derive(Debug)]
+pub struct Consumer<'a, T, const N: usize> {
+  ring: &'a SpscRing<T, N>,
+}
+impl<'a, T, const N: usize> Consumer<'a, T, N> {
+  /// Takes the oldest item, if any.
+  pub fn dequeue(&mut self) -> Option<T> {
+    let head = self.ring.head.load(Ordering::Relaxed);
+    if head == self.ring.tail.load(Ordering::Acquire) {
+      return None;
+    }
+    let item = unsafe { self.ring.slot(head).read() };
+    self.ring.head.store((head + 1) % N, Ordering::Release);
+    Some(item)
+  }
+
+  /// If the ring is empty.
+  pub fn is_empty(&self) -> bool {
+    self.ring.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+
+  #[test]
+  fn ring() {
+    let mut ring: SpscRing<u32, 4> = SpscRing::new();
+    assert_eq!(ring.capacity(), 3);
+    let (mut producer, mut consumer) = ring.split();
+    // Go around a few times so the indexes wrap.
+    for round in 0..3 {
+      assert!(consumer.is_empty());
+      for i in 0..3 {
+        assert_eq!(producer.enqueue(round * 10 + i), Ok(()));
+      }
+      assert!(producer.is_full());
+      assert_eq!(producer.enqueue(99), Err(99));
+      for i in 0..3 {
+        assert_eq!(consumer.dequeue(), Some(round * 10 + i));
+      }
+      assert_eq!(consumer.dequeue(), None);
+    }
+    producer.enqueue(7).unwrap();
+    assert_eq!(ring.len(), 1);
+  }
+
+  #[test]
+  fn ring_drops_items() {
+    let item = Rc::new(());
+    {
+      let mut ring: SpscRing<Rc<()>, 3> = SpscRing::new();
+      let (mut producer, mut consumer) = ring.split();
+      producer.enqueue(item.clone()).unwrap();
+      producer.enqueue(item.clone()).unwrap();
+      drop(consumer.dequeue());
+      assert_eq!(Rc::strong_count(&item), 2);
+    }
+    assert_eq!(Rc::strong_count(&item), 1);
+  }
+
+  #[test]
+  fn irq_mutex() {
+    let cs = unsafe { CriticalSection::new() };
+    let cell = IrqCell::new(1);
+    assert_eq!(cell.replace(&cs, 2), 1);
+    assert_eq!(cell.get(&cs), 2);
+    let mutex = IrqMutex::new(5);
+    assert_eq!(mutex.lock(&cs, |value| core::mem::replace(value, 6)), 5);
+    assert_eq!(mutex.lock(&cs, |_| mutex.try_lock(&cs, |_| ())), None);
+    assert_eq!(mutex.into_inner(), 6);
+  }
+}